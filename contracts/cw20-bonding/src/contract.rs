@@ -1,33 +1,50 @@
 #[cfg(not(feature = "library"))]
 use cosmwasm_std::entry_point;
 use cosmwasm_std::{
-    attr, coins, to_binary, Addr, BankMsg, Binary, Deps, DepsMut, Env, MessageInfo, Response,
-    StdError, StdResult, Uint128,
+    attr, coins, from_binary, to_binary, Addr, BankMsg, Binary, CosmosMsg, Deps, DepsMut, Env,
+    MessageInfo, Response, StdError, StdResult, Uint128, WasmMsg,
 };
 
-use cw2::set_contract_version;
+use cw2::{get_contract_version, set_contract_version};
+use cw20::{
+    Cw20ExecuteMsg, Cw20ReceiveMsg, Denom, EmbeddedLogo, Logo, LogoInfo, MarketingInfoResponse,
+};
 use cw20_base::allowances::{
     deduct_allowance, execute_decrease_allowance, execute_increase_allowance, execute_send_from,
     execute_transfer_from, query_allowance,
 };
 use cw20_base::contract::{
-    execute_burn, execute_mint, execute_send, execute_transfer, query_balance, query_token_info,
+    execute_burn, execute_mint, execute_send, execute_transfer, execute_update_marketing,
+    execute_upload_logo, query_balance, query_download_logo, query_marketing_info,
+    query_token_info,
 };
-use cw20_base::state::{MinterData, TokenInfo, TOKEN_INFO};
+use cw20_base::enumerable::{query_all_accounts, query_owner_allowances, query_spender_allowances};
+use cw20_base::msg::InstantiateMarketingInfo;
+use cw20_base::state::{MinterData, TokenInfo, LOGO, MARKETING_INFO, TOKEN_INFO};
 
-use crate::curves::DecimalPlaces;
+use crate::curves::{checked_decimal, Constant, Curve, DecimalPlaces};
 use crate::error::ContractError;
-use crate::msg::{CurveFn, CurveInfoResponse, ExecuteMsg, InstantiateMsg, QueryMsg};
-use crate::state::{CurveState, CURVE_STATE, CURVE_TYPE};
+use crate::migrations::v0_14_2;
+use crate::msg::{
+    CurveFn, CurveInfoResponse, CurveType, ExecuteMsg, HatchConfig, HatchResponse, HatcherResponse,
+    InstantiateMsg, MigrateMsg, QueryMsg, ReceiveMsg, ReserveAsset,
+};
+use crate::state::{
+    CurveState, Hatch, Hatcher, Phase, ADMIN, BOUGHT, CURVE_STATE, CURVE_TYPE, HATCH, HATCHERS,
+};
 use cw_utils::{must_pay, nonpayable};
+use semver::Version;
 
 // version info for migration info
 const CONTRACT_NAME: &str = "crates.io:cw20-bonding";
 const CONTRACT_VERSION: &str = env!("CARGO_PKG_VERSION");
 
+/// Fees are expressed in basis points, this is 100%
+const MAX_FEE_BPS: u16 = 10_000;
+
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn instantiate(
-    deps: DepsMut,
+    mut deps: DepsMut,
     env: Env,
     info: MessageInfo,
     msg: InstantiateMsg,
@@ -43,21 +60,152 @@ pub fn instantiate(
         total_supply: Uint128::zero(),
         // set self as minter, so we can properly execute mint and burn
         mint: Some(MinterData {
-            minter: env.contract.address,
-            cap: None,
+            minter: env.contract.address.clone(),
+            cap: msg.max_supply,
         }),
     };
     TOKEN_INFO.save(deps.storage, &data)?;
+    if let Some(marketing) = msg.marketing {
+        save_marketing(deps.branch(), marketing)?;
+    }
+
+    if msg.buy_fee_bps >= MAX_FEE_BPS || msg.sell_fee_bps >= MAX_FEE_BPS {
+        return Err(ContractError::InvalidFee { max: MAX_FEE_BPS });
+    }
+    let fee_recipient = msg
+        .fee_recipient
+        .map(|addr| deps.api.addr_validate(&addr))
+        .transpose()?;
+    if fee_recipient.is_none() && (msg.buy_fee_bps > 0 || msg.sell_fee_bps > 0) {
+        return Err(ContractError::MissingFeeRecipient {});
+    }
+
+    let limits = [msg.max_supply, msg.max_buy, msg.max_buy_per_address];
+    if limits.iter().flatten().any(Uint128::is_zero) {
+        return Err(ContractError::InvalidLimit {});
+    }
+
+    let reserve_denom = match msg.reserve_asset {
+        ReserveAsset::Native { denom } => Denom::Native(denom),
+        ReserveAsset::Cw20 { address } => Denom::Cw20(deps.api.addr_validate(&address)?),
+    };
 
     let places = DecimalPlaces::new(msg.decimals, msg.reserve_decimals);
-    let supply = CurveState::new(msg.reserve_denom, places);
+    let mut supply = CurveState::new(reserve_denom, places);
+    supply.buy_fee_bps = msg.buy_fee_bps;
+    supply.sell_fee_bps = msg.sell_fee_bps;
+    supply.fee_recipient = fee_recipient;
+    supply.max_buy = msg.max_buy;
+    supply.max_buy_per_address = msg.max_buy_per_address;
+    if let Some(hatch) = msg.hatch {
+        save_hatch(deps.branch(), hatch)?;
+        supply.phase = Phase::Hatch;
+    }
     CURVE_STATE.save(deps.storage, &supply)?;
 
+    msg.curve_type.validate()?;
     CURVE_TYPE.save(deps.storage, &msg.curve_type)?;
 
+    let admin = msg
+        .admin
+        .map(|addr| deps.api.addr_validate(&addr))
+        .transpose()?;
+    ADMIN.set(deps, admin)?;
+
     Ok(Response::default())
 }
 
+fn save_marketing(deps: DepsMut, marketing: InstantiateMarketingInfo) -> Result<(), ContractError> {
+    // stored the same way as cw20-base instantiate does
+    let logo = match marketing.logo {
+        Some(logo) => {
+            verify_logo(&logo)?;
+            LOGO.save(deps.storage, &logo)?;
+            match logo {
+                Logo::Url(url) => Some(LogoInfo::Url(url)),
+                Logo::Embedded(_) => Some(LogoInfo::Embedded),
+            }
+        }
+        None => None,
+    };
+    let data = MarketingInfoResponse {
+        project: marketing.project,
+        description: marketing.description,
+        marketing: marketing
+            .marketing
+            .map(|addr| deps.api.addr_validate(&addr))
+            .transpose()?,
+        logo,
+    };
+    MARKETING_INFO.save(deps.storage, &data)?;
+    Ok(())
+}
+
+/// The same checks cw20-base applies to uploaded logos, which it does not export
+fn verify_logo(logo: &Logo) -> Result<(), cw20_base::ContractError> {
+    const LOGO_SIZE_CAP: usize = 5 * 1024;
+    const PNG_HEADER: [u8; 8] = [0x89, b'P', b'N', b'G', 0x0d, 0x0a, 0x1a, 0x0a];
+
+    match logo {
+        Logo::Embedded(EmbeddedLogo::Svg(logo)) => {
+            let preamble = logo.split_inclusive(|c| *c == b'>').next().unwrap_or(&[]);
+            if !(preamble.starts_with(b"<?xml ") && preamble.ends_with(b"?>")) {
+                return Err(cw20_base::ContractError::InvalidXmlPreamble {});
+            }
+            if logo.len() > LOGO_SIZE_CAP {
+                return Err(cw20_base::ContractError::LogoTooBig {});
+            }
+        }
+        Logo::Embedded(EmbeddedLogo::Png(logo)) => {
+            if logo.len() > LOGO_SIZE_CAP {
+                return Err(cw20_base::ContractError::LogoTooBig {});
+            }
+            if !logo.starts_with(&PNG_HEADER) {
+                return Err(cw20_base::ContractError::InvalidPngHeader {});
+            }
+        }
+        Logo::Url(_) => {}
+    }
+    Ok(())
+}
+
+fn save_hatch(deps: DepsMut, config: HatchConfig) -> Result<(), ContractError> {
+    if config.price.is_zero() {
+        return Err(ContractError::InvalidHatch {
+            reason: "price must be greater than zero".to_string(),
+        });
+    }
+    checked_decimal(config.price, config.scale)?;
+    if config.funding_cap.is_zero() {
+        return Err(ContractError::InvalidHatch {
+            reason: "funding cap must be greater than zero".to_string(),
+        });
+    }
+    if config.funding_pool_bps >= MAX_FEE_BPS {
+        return Err(ContractError::InvalidHatch {
+            reason: format!("funding pool share must be less than {MAX_FEE_BPS} basis points"),
+        });
+    }
+
+    let hatch = Hatch {
+        price: config.price,
+        scale: config.scale,
+        funding_cap: config.funding_cap,
+        funding_pool: deps.api.addr_validate(&config.funding_pool)?,
+        funding_pool_bps: config.funding_pool_bps,
+        vesting_seconds: config.vesting_seconds,
+        raised: Uint128::zero(),
+        closed_at: None,
+    };
+    HATCH.save(deps.storage, &hatch)?;
+
+    for addr in config.allowlist {
+        let addr = deps.api.addr_validate(&addr)?;
+        HATCHERS.save(deps.storage, &addr, &Hatcher::default())?;
+    }
+    Ok(())
+}
+
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn execute(
     deps: DepsMut,
@@ -83,7 +231,27 @@ pub fn do_execute(
     curve_fn: CurveFn,
 ) -> Result<Response, ContractError> {
     match msg {
-        ExecuteMsg::Buy {} => execute_buy(deps, env, info, curve_fn),
+        ExecuteMsg::Buy {} => execute_buy(deps, env, info, curve_fn, Delivery::Buyer),
+        ExecuteMsg::BuyFor { recipient } => {
+            let recipient = deps.api.addr_validate(&recipient)?;
+            let delivery = Delivery::Recipient(recipient);
+            execute_buy(deps, env, info, curve_fn, delivery)
+        }
+        ExecuteMsg::BuyAndSend { contract, msg } => {
+            let contract = deps.api.addr_validate(&contract)?;
+            let delivery = Delivery::Contract { contract, msg };
+            execute_buy(deps, env, info, curve_fn, delivery)
+        }
+        ExecuteMsg::Receive(msg) => execute_receive(deps, env, info, curve_fn, msg),
+        ExecuteMsg::SwitchCurve { curve_type } => execute_switch_curve(deps, info, curve_type),
+        ExecuteMsg::UpdateAdmin { admin } => {
+            let admin = admin
+                .map(|addr| deps.api.addr_validate(&addr))
+                .transpose()?;
+            Ok(ADMIN.execute_update_admin(deps, info, admin)?)
+        }
+        ExecuteMsg::CloseHatch {} => execute_close_hatch(deps, env, info),
+        ExecuteMsg::ClaimVested {} => execute_claim_vested(deps, env, info),
 
         // we override these from cw20
         ExecuteMsg::Burn { amount } => Ok(execute_sell(deps, env, info, curve_fn, amount)?),
@@ -129,45 +297,359 @@ pub fn do_execute(
         } => Ok(execute_send_from(
             deps, env, info, owner, contract, amount, msg,
         )?),
+        ExecuteMsg::UpdateMarketing {
+            project,
+            description,
+            marketing,
+        } => Ok(execute_update_marketing(
+            deps,
+            env,
+            info,
+            project,
+            description,
+            marketing,
+        )?),
+        ExecuteMsg::UploadLogo(logo) => Ok(execute_upload_logo(deps, env, info, logo)?),
     }
 }
 
+/// Where the supply tokens of a buy end up
+pub enum Delivery {
+    /// minted to whoever paid
+    Buyer,
+    /// minted to another account
+    Recipient(Addr),
+    /// minted to a contract and announced with a `Cw20ReceiveMsg`, like `Send`
+    Contract { contract: Addr, msg: Binary },
+}
+
 pub fn execute_buy(
     deps: DepsMut,
     env: Env,
     info: MessageInfo,
     curve_fn: CurveFn,
+    delivery: Delivery,
+) -> Result<Response, ContractError> {
+    let state = CURVE_STATE.load(deps.storage)?;
+    let payment = match &state.reserve_denom {
+        Denom::Native(denom) => must_pay(&info, denom)?,
+        Denom::Cw20(_) => return Err(ContractError::Cw20Reserve {}),
+    };
+
+    do_buy(deps, env, curve_fn, info.sender, payment, delivery)
+}
+
+pub fn execute_receive(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    curve_fn: CurveFn,
+    wrapper: Cw20ReceiveMsg,
+) -> Result<Response, ContractError> {
+    nonpayable(&info)?;
+    // only the reserve token contract may call this
+    let state = CURVE_STATE.load(deps.storage)?;
+    match &state.reserve_denom {
+        Denom::Cw20(addr) if *addr == info.sender => {}
+        Denom::Cw20(_) => return Err(ContractError::Unauthorized {}),
+        Denom::Native(_) => return Err(ContractError::NativeReserve {}),
+    }
+
+    let buyer = deps.api.addr_validate(&wrapper.sender)?;
+    let delivery = match from_binary(&wrapper.msg)? {
+        ReceiveMsg::Buy {} => Delivery::Buyer,
+        ReceiveMsg::BuyFor { recipient } => {
+            Delivery::Recipient(deps.api.addr_validate(&recipient)?)
+        }
+        ReceiveMsg::BuyAndSend { contract, msg } => Delivery::Contract {
+            contract: deps.api.addr_validate(&contract)?,
+            msg,
+        },
+    };
+    do_buy(deps, env, curve_fn, buyer, wrapper.amount, delivery)
+}
+
+/// Mints supply tokens for the reserve tokens already received by the contract from the buyer
+fn do_buy(
+    deps: DepsMut,
+    env: Env,
+    curve_fn: CurveFn,
+    buyer: Addr,
+    payment: Uint128,
+    delivery: Delivery,
 ) -> Result<Response, ContractError> {
     let mut state = CURVE_STATE.load(deps.storage)?;
+    if state.phase == Phase::Hatch {
+        return do_hatch_buy(deps, env, state, buyer, payment, delivery);
+    }
 
-    let payment = must_pay(&info, &state.reserve_denom)?;
+    // take the fee out before anything goes into the reserve
+    let mut fee = fee_amount(payment, state.buy_fee_bps);
+    let mut net = payment - fee;
 
-    // calculate how many tokens can be purchased with this and mint them
+    // calculate how many tokens can be purchased with this
     let curve = curve_fn(state.clone().decimals);
-    state.reserve += payment;
-    let new_supply = curve.supply(state.reserve);
-    let minted = new_supply
+    let mut minted = curve
+        .supply(state.reserve + net)?
         .checked_sub(state.supply)
         .map_err(StdError::overflow)?;
-    state.supply = new_supply;
+
+    // only mint up to the limits, and only charge the reserve needed for that.
+    // the per address limit counts for the recipient when buying for someone else
+    let holder = match &delivery {
+        Delivery::Recipient(recipient) => recipient.clone(),
+        _ => buyer.clone(),
+    };
+    let allowed = buy_allowance(deps.as_ref(), &state, &holder)?;
+    let mut refund = Uint128::zero();
+    if let Some(allowed) = allowed {
+        if allowed.is_zero() {
+            return Err(ContractError::BuyLimitReached {});
+        }
+        if minted > allowed {
+            minted = allowed;
+            let needed = curve
+                .reserve(state.supply + minted)?
+                .saturating_sub(state.reserve)
+                .min(net);
+            fee = fee.multiply_ratio(needed, net);
+            refund = payment - needed - fee;
+            net = needed;
+        }
+    }
+
+    state.fees_collected += fee;
+    state.reserve += net;
+    state.supply += minted;
     CURVE_STATE.save(deps.storage, &state)?;
+    if state.max_buy_per_address.is_some() {
+        BOUGHT.update(deps.storage, &holder, |bought| -> StdResult<_> {
+            Ok(bought.unwrap_or_default() + minted)
+        })?;
+    }
 
     // call into cw20-base to mint the token, call as self as no one else is allowed
     let sub_info = MessageInfo {
         sender: env.contract.address.clone(),
         funds: vec![],
     };
-    execute_mint(deps, env, sub_info, info.sender.to_string(), minted)?;
+    let recipient = match &delivery {
+        Delivery::Buyer => buyer.clone(),
+        Delivery::Recipient(recipient) => recipient.clone(),
+        Delivery::Contract { contract, .. } => contract.clone(),
+    };
+    execute_mint(deps, env, sub_info, recipient.to_string(), minted)?;
 
-    // bond them to the validator
-    let res = Response::new()
+    let mut res = Response::new();
+    if !refund.is_zero() {
+        res = res.add_message(send_reserve(&state.reserve_denom, &buyer, refund)?);
+    }
+    res = res.add_messages(fee_msg(&state, fee)?);
+    if let Delivery::Contract { contract, msg } = delivery {
+        let receive = Cw20ReceiveMsg {
+            sender: buyer.to_string(),
+            amount: minted,
+            msg,
+        };
+        res = res.add_message(receive.into_cosmos_msg(contract)?);
+    }
+    let res = res
         .add_attribute("action", "buy")
-        .add_attribute("from", info.sender)
-        .add_attribute("reserve", payment)
-        .add_attribute("supply", minted);
+        .add_attribute("from", buyer)
+        .add_attribute("to", recipient)
+        .add_attribute("reserve", net)
+        .add_attribute("supply", minted)
+        .add_attribute("fee", fee)
+        .add_attribute("refund", refund);
     Ok(res)
 }
 
+/// Returns how many more supply tokens `buyer` may buy right now, None if there is no limit
+fn buy_allowance(
+    deps: Deps,
+    state: &CurveState,
+    buyer: &Addr,
+) -> Result<Option<Uint128>, ContractError> {
+    let cap = TOKEN_INFO.load(deps.storage)?.get_cap();
+    let per_address = match state.max_buy_per_address {
+        Some(limit) => {
+            let bought = BOUGHT.may_load(deps.storage, buyer)?.unwrap_or_default();
+            Some(limit.saturating_sub(bought))
+        }
+        None => None,
+    };
+    let allowed = [
+        cap.map(|cap| cap.saturating_sub(state.supply)),
+        state.max_buy,
+        per_address,
+    ]
+    .iter()
+    .flatten()
+    .min()
+    .copied();
+    Ok(allowed)
+}
+
+/// Buys at the fixed hatch price. Part of the payment goes to the funding pool, the rest to the
+/// reserve, and the tokens are held by the contract until they vest
+fn do_hatch_buy(
+    mut deps: DepsMut,
+    env: Env,
+    mut state: CurveState,
+    buyer: Addr,
+    payment: Uint128,
+    delivery: Delivery,
+) -> Result<Response, ContractError> {
+    // hatch tokens stay in the contract until they vest, so they cannot be sent on
+    let buyer = match delivery {
+        Delivery::Buyer => buyer,
+        Delivery::Recipient(recipient) => recipient,
+        Delivery::Contract { .. } => return Err(ContractError::HatchActive {}),
+    };
+    let mut hatcher = HATCHERS
+        .may_load(deps.storage, &buyer)?
+        .ok_or(ContractError::NotHatcher {})?;
+    let mut hatch = HATCH.load(deps.storage)?;
+
+    let remaining = hatch.funding_cap - hatch.raised;
+    if payment > remaining {
+        return Err(ContractError::HatchCapExceeded { remaining });
+    }
+    hatch.raised += payment;
+
+    let to_pool = fee_amount(payment, hatch.funding_pool_bps);
+    let price = checked_decimal(hatch.price, hatch.scale)?;
+    let minted = Constant::new(price, state.decimals.clone()).supply(payment)?;
+    if minted.is_zero() {
+        return Err(cw20_base::ContractError::InvalidZeroAmount {}.into());
+    }
+    state.reserve += payment - to_pool;
+    state.supply += minted;
+    CURVE_STATE.save(deps.storage, &state)?;
+
+    hatcher.contributed += payment;
+    hatcher.tokens += minted;
+    HATCHERS.save(deps.storage, &buyer, &hatcher)?;
+
+    // mint to ourselves, we release them as they vest
+    let sub_info = MessageInfo {
+        sender: env.contract.address.clone(),
+        funds: vec![],
+    };
+    execute_mint(
+        deps.branch(),
+        env.clone(),
+        sub_info,
+        env.contract.address.to_string(),
+        minted,
+    )?;
+
+    let mut res = Response::new();
+    if !to_pool.is_zero() {
+        res = res.add_message(send_reserve(
+            &state.reserve_denom,
+            &hatch.funding_pool,
+            to_pool,
+        )?);
+    }
+    if hatch.raised == hatch.funding_cap {
+        close_hatch(deps, &env, hatch)?;
+        res = res.add_attribute("hatch", "closed");
+    } else {
+        HATCH.save(deps.storage, &hatch)?;
+    }
+
+    Ok(res
+        .add_attribute("action", "hatch")
+        .add_attribute("from", buyer)
+        .add_attribute("reserve", payment - to_pool)
+        .add_attribute("funding_pool", to_pool)
+        .add_attribute("supply", minted))
+}
+
+pub fn execute_close_hatch(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+) -> Result<Response, ContractError> {
+    nonpayable(&info)?;
+    ADMIN.assert_admin(deps.as_ref(), &info.sender)?;
+    let hatch = HATCH
+        .may_load(deps.storage)?
+        .filter(|hatch| hatch.closed_at.is_none())
+        .ok_or(ContractError::HatchClosed {})?;
+    close_hatch(deps, &env, hatch)?;
+    Ok(Response::new().add_attribute("action", "close_hatch"))
+}
+
+/// Ends the hatch, starts vesting and fits the configured curve to the raised reserve,
+/// if it can be fit
+fn close_hatch(deps: DepsMut, env: &Env, mut hatch: Hatch) -> Result<(), ContractError> {
+    hatch.closed_at = Some(env.block.time);
+    HATCH.save(deps.storage, &hatch)?;
+
+    let mut state = CURVE_STATE.load(deps.storage)?;
+    state.phase = Phase::Open;
+    CURVE_STATE.save(deps.storage, &state)?;
+
+    // a derived contract may bring its own curve instead of storing a CurveType.
+    // the hatch must always be able to close, so keep the configured curve if it cannot be fit
+    if let Some(curve_type) = CURVE_TYPE.may_load(deps.storage)? {
+        let curve_type = fit_curve(&state, curve_type.clone()).unwrap_or(curve_type);
+        CURVE_TYPE.save(deps.storage, &curve_type)?;
+    }
+    Ok(())
+}
+
+pub fn execute_claim_vested(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+) -> Result<Response, ContractError> {
+    nonpayable(&info)?;
+    let hatch = HATCH.load(deps.storage)?;
+    let mut hatcher = HATCHERS
+        .may_load(deps.storage, &info.sender)?
+        .ok_or(ContractError::NotHatcher {})?;
+
+    let claimable = claimable_hatch_tokens(&hatch, &hatcher, &env)?;
+    if claimable.is_zero() {
+        return Err(ContractError::NothingToClaim {});
+    }
+    hatcher.claimed += claimable;
+    HATCHERS.save(deps.storage, &info.sender, &hatcher)?;
+
+    // release the tokens we have been holding
+    let sub_info = MessageInfo {
+        sender: env.contract.address.clone(),
+        funds: vec![],
+    };
+    execute_transfer(deps, env, sub_info, info.sender.to_string(), claimable)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "claim_vested")
+        .add_attribute("to", info.sender)
+        .add_attribute("amount", claimable))
+}
+
+/// Hatch tokens vest linearly from the close of the hatch, nothing vests while it is open
+fn claimable_hatch_tokens(hatch: &Hatch, hatcher: &Hatcher, env: &Env) -> StdResult<Uint128> {
+    let vested = match hatch.closed_at {
+        None => Uint128::zero(),
+        Some(closed_at) => {
+            let elapsed = env.block.time.seconds().saturating_sub(closed_at.seconds());
+            if elapsed >= hatch.vesting_seconds {
+                hatcher.tokens
+            } else {
+                hatcher
+                    .tokens
+                    .multiply_ratio(elapsed, hatch.vesting_seconds)
+            }
+        }
+    };
+    Ok(vested.checked_sub(hatcher.claimed)?)
+}
+
 pub fn execute_sell(
     deps: DepsMut,
     env: Env,
@@ -231,37 +713,125 @@ fn do_sell(
     receiver: Addr,
     amount: Uint128,
 ) -> Result<Response, ContractError> {
+    // nothing can be sold back until the hatch is over
+    let mut state = CURVE_STATE.load(deps.storage)?;
+    if state.phase == Phase::Hatch {
+        return Err(ContractError::HatchActive {});
+    }
+
     // burn from the caller, this ensures there are tokens to cover this
     execute_burn(deps.branch(), env, info.clone(), amount)?;
 
     // calculate how many tokens can be purchased with this and mint them
-    let mut state = CURVE_STATE.load(deps.storage)?;
     let curve = curve_fn(state.clone().decimals);
     state.supply = state
         .supply
         .checked_sub(amount)
         .map_err(StdError::overflow)?;
-    let new_reserve = curve.reserve(state.supply);
+    let new_reserve = curve.reserve(state.supply)?;
     let released = state
         .reserve
         .checked_sub(new_reserve)
         .map_err(StdError::overflow)?;
     state.reserve = new_reserve;
+
+    // the fee is taken from what the curve releases
+    let fee = fee_amount(released, state.sell_fee_bps);
+    let payout = released - fee;
+    state.fees_collected += fee;
     CURVE_STATE.save(deps.storage, &state)?;
 
     // now send the tokens to the sender (TODO: for sell_from we do something else, right???)
-    let msg = BankMsg::Send {
-        to_address: receiver.to_string(),
-        amount: coins(released.u128(), state.reserve_denom),
-    };
+    let msg = send_reserve(&state.reserve_denom, &receiver, payout)?;
     let res = Response::new()
         .add_message(msg)
+        .add_messages(fee_msg(&state, fee)?)
         .add_attribute("from", info.sender)
         .add_attribute("supply", amount)
-        .add_attribute("reserve", released);
+        .add_attribute("reserve", payout)
+        .add_attribute("fee", fee);
     Ok(res)
 }
 
+pub fn execute_switch_curve(
+    deps: DepsMut,
+    info: MessageInfo,
+    curve_type: CurveType,
+) -> Result<Response, ContractError> {
+    nonpayable(&info)?;
+    ADMIN.assert_admin(deps.as_ref(), &info.sender)?;
+    curve_type.validate()?;
+
+    let state = CURVE_STATE.load(deps.storage)?;
+    let curve_type = fit_curve(&state, curve_type)?;
+    CURVE_TYPE.save(deps.storage, &curve_type)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "switch_curve")
+        .add_attribute("curve_type", format!("{:?}", curve_type))
+        .add_attribute("supply", state.supply)
+        .add_attribute("reserve", state.reserve))
+}
+
+/// Keeps the shape of the given curve, but scales it so the outstanding supply is still
+/// backed by exactly the reserve we hold
+fn fit_curve(state: &CurveState, curve_type: CurveType) -> Result<CurveType, ContractError> {
+    let curve_type = if state.supply.is_zero() {
+        curve_type
+    } else {
+        let unscaled = curve_type.to_curve_fn()(state.decimals.clone()).reserve(state.supply)?;
+        if unscaled.is_zero() {
+            return Err(ContractError::CurveMismatch {});
+        }
+        curve_type
+            .rescale(state.reserve, unscaled)
+            .ok_or(ContractError::CurveMismatch {})?
+    };
+
+    // rounding must never let the curve promise more than the reserve holds
+    let curve = curve_type.to_curve_fn()(state.decimals.clone());
+    if curve.reserve(state.supply)? > state.reserve {
+        return Err(ContractError::CurveMismatch {});
+    }
+    Ok(curve_type)
+}
+
+/// Returns the part of `amount` taken as a fee, rounded down in favour of the user
+fn fee_amount(amount: Uint128, fee_bps: u16) -> Uint128 {
+    amount.multiply_ratio(fee_bps, MAX_FEE_BPS)
+}
+
+/// Sends the fee to the fee recipient, if there is anything to send
+fn fee_msg(state: &CurveState, fee: Uint128) -> StdResult<Option<CosmosMsg>> {
+    match &state.fee_recipient {
+        Some(recipient) if !fee.is_zero() => {
+            Ok(Some(send_reserve(&state.reserve_denom, recipient, fee)?))
+        }
+        _ => Ok(None),
+    }
+}
+
+/// Pays out reserve tokens, with a bank send or a cw20 transfer depending on the reserve
+fn send_reserve(denom: &Denom, to: &Addr, amount: Uint128) -> StdResult<CosmosMsg> {
+    let msg = match denom {
+        Denom::Native(denom) => BankMsg::Send {
+            to_address: to.to_string(),
+            amount: coins(amount.u128(), denom),
+        }
+        .into(),
+        Denom::Cw20(addr) => WasmMsg::Execute {
+            contract_addr: addr.to_string(),
+            msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                recipient: to.to_string(),
+                amount,
+            })?,
+            funds: vec![],
+        }
+        .into(),
+    };
+    Ok(msg)
+}
+
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> StdResult<Binary> {
     // default implementation stores curve info as enum, you can do something else in a derived
@@ -274,16 +844,40 @@ pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> StdResult<Binary> {
 /// We pull out logic here, so we can import this from another contract and set a different Curve.
 /// This contacts sets a curve with an enum in InstantitateMsg and stored in state, but you may want
 /// to use custom math not included - make this easily reusable
-pub fn do_query(deps: Deps, _env: Env, msg: QueryMsg, curve_fn: CurveFn) -> StdResult<Binary> {
+pub fn do_query(deps: Deps, env: Env, msg: QueryMsg, curve_fn: CurveFn) -> StdResult<Binary> {
     match msg {
         // custom queries
         QueryMsg::CurveInfo {} => to_binary(&query_curve_info(deps, curve_fn)?),
+        QueryMsg::CurveType {} => to_binary(&CURVE_TYPE.load(deps.storage)?),
+        QueryMsg::Admin {} => to_binary(&ADMIN.query_admin(deps)?),
+        QueryMsg::Hatch {} => to_binary(&query_hatch(deps)?),
+        QueryMsg::Hatcher { address } => to_binary(&query_hatcher(deps, env, address)?),
         // inherited from cw20-base
         QueryMsg::TokenInfo {} => to_binary(&query_token_info(deps)?),
         QueryMsg::Balance { address } => to_binary(&query_balance(deps, address)?),
         QueryMsg::Allowance { owner, spender } => {
             to_binary(&query_allowance(deps, owner, spender)?)
         }
+        QueryMsg::AllAllowances {
+            owner,
+            start_after,
+            limit,
+        } => to_binary(&query_owner_allowances(deps, owner, start_after, limit)?),
+        QueryMsg::AllSpenderAllowances {
+            spender,
+            start_after,
+            limit,
+        } => to_binary(&query_spender_allowances(
+            deps,
+            spender,
+            start_after,
+            limit,
+        )?),
+        QueryMsg::AllAccounts { start_after, limit } => {
+            to_binary(&query_all_accounts(deps, start_after, limit)?)
+        }
+        QueryMsg::MarketingInfo {} => to_binary(&query_marketing_info(deps)?),
+        QueryMsg::DownloadLogo {} => to_binary(&query_download_logo(deps)?),
     }
 }
 
@@ -293,27 +887,107 @@ pub fn query_curve_info(deps: Deps, curve_fn: CurveFn) -> StdResult<CurveInfoRes
         supply,
         reserve_denom,
         decimals,
+        buy_fee_bps,
+        sell_fee_bps,
+        fee_recipient,
+        fees_collected,
+        phase,
+        max_buy,
+        max_buy_per_address,
     } = CURVE_STATE.load(deps.storage)?;
+    let max_supply = TOKEN_INFO.load(deps.storage)?.get_cap();
 
     // This we can get from the local digits stored in instantiate
     let curve = curve_fn(decimals);
-    let spot_price = curve.spot_price(supply);
+    let spot_price = curve
+        .spot_price(supply)
+        .map_err(|err| StdError::generic_err(err.to_string()))?;
 
     Ok(CurveInfoResponse {
         reserve,
         supply,
         spot_price,
         reserve_denom,
+        buy_fee_bps,
+        sell_fee_bps,
+        fee_recipient: fee_recipient.map(|addr| addr.into_string()),
+        fees_collected,
+        phase,
+        max_supply,
+        max_buy,
+        max_buy_per_address,
     })
 }
 
+pub fn query_hatch(deps: Deps) -> StdResult<HatchResponse> {
+    let hatch = HATCH.load(deps.storage)?;
+    Ok(HatchResponse {
+        price: hatch.price,
+        scale: hatch.scale,
+        funding_cap: hatch.funding_cap,
+        funding_pool: hatch.funding_pool.into_string(),
+        funding_pool_bps: hatch.funding_pool_bps,
+        vesting_seconds: hatch.vesting_seconds,
+        raised: hatch.raised,
+        closed_at: hatch.closed_at,
+    })
+}
+
+pub fn query_hatcher(deps: Deps, env: Env, address: String) -> StdResult<HatcherResponse> {
+    let address = deps.api.addr_validate(&address)?;
+    let hatch = HATCH.load(deps.storage)?;
+    let hatcher = HATCHERS.load(deps.storage, &address)?;
+    let claimable = claimable_hatch_tokens(&hatch, &hatcher, &env)?;
+    Ok(HatcherResponse {
+        contributed: hatcher.contributed,
+        tokens: hatcher.tokens,
+        claimed: hatcher.claimed,
+        claimable,
+    })
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn migrate(mut deps: DepsMut, _env: Env, msg: MigrateMsg) -> Result<Response, ContractError> {
+    let contract_info = get_contract_version(deps.storage)?;
+    if contract_info.contract != CONTRACT_NAME {
+        return Err(ContractError::CannotMigrate {
+            previous_contract: contract_info.contract,
+        });
+    }
+    let contract_version: Version = contract_info.version.parse()?;
+    let current_version: Version = CONTRACT_VERSION.parse()?;
+    if contract_version > current_version {
+        return Err(ContractError::CannotMigrateVersion {
+            previous_version: contract_info.version,
+        });
+    }
+
+    set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
+    // the reserve denom was stored as a native coin name up to 0.14.2
+    if contract_version <= Version::new(0, 14, 2) {
+        v0_14_2::migrate_curve_state(deps.branch())?;
+    }
+    if let Some(admin) = msg.admin {
+        let admin = deps.api.addr_validate(&admin)?;
+        ADMIN.set(deps, Some(admin))?;
+    }
+    Ok(Response::new().add_attribute("action", "migrate"))
+}
+
 // this is poor mans "skip" flag
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::msg::CurveType;
+    use crate::msg::{CurveType, HatchConfig};
     use cosmwasm_std::testing::{mock_dependencies, mock_env, mock_info};
-    use cosmwasm_std::{coin, Decimal, OverflowError, OverflowOperation, StdError, SubMsg};
+    use cosmwasm_std::{
+        coin, Decimal, OverflowError, OverflowOperation, StdError, Storage, SubMsg,
+    };
+    use cw20::{
+        AllAccountsResponse, AllAllowancesResponse, AllSpenderAllowancesResponse, EmbeddedLogo,
+        Logo, LogoInfo,
+    };
+    use cw_controllers::AdminError;
     use cw_utils::PaymentError;
 
     const DENOM: &str = "satoshi";
@@ -330,8 +1004,19 @@ mod tests {
             name: "Bonded".to_string(),
             symbol: "EPOXY".to_string(),
             decimals,
-            reserve_denom: DENOM.to_string(),
+            reserve_asset: ReserveAsset::Native {
+                denom: DENOM.to_string(),
+            },
             reserve_decimals,
+            buy_fee_bps: 0,
+            sell_fee_bps: 0,
+            fee_recipient: None,
+            max_supply: None,
+            max_buy: None,
+            max_buy_per_address: None,
+            hatch: None,
+            admin: Some(CREATOR.to_string()),
+            marketing: None,
             curve_type,
         }
     }
@@ -379,7 +1064,7 @@ mod tests {
         let state = query_curve_info(deps.as_ref(), curve_type.to_curve_fn()).unwrap();
         assert_eq!(state.reserve, Uint128::zero());
         assert_eq!(state.supply, Uint128::zero());
-        assert_eq!(state.reserve_denom.as_str(), DENOM);
+        assert_eq!(state.reserve_denom, Denom::Native(DENOM.to_string()));
         // spot price 0 as supply is 0
         assert_eq!(state.spot_price, Decimal::zero());
 
@@ -529,6 +1214,527 @@ mod tests {
         assert_eq!(token.total_supply, Uint128::new(1000));
     }
 
+    #[test]
+    fn fees_go_to_recipient() {
+        let mut deps = mock_dependencies();
+        let curve_type = CurveType::Linear {
+            slope: Uint128::new(1),
+            scale: 1,
+        };
+        let mut msg = default_instantiate(2, 8, curve_type.clone());
+        msg.buy_fee_bps = 100;
+        msg.sell_fee_bps = 250;
+        msg.fee_recipient = Some("dao".to_string());
+        instantiate(deps.as_mut(), mock_env(), mock_info(CREATOR, &[]), msg).unwrap();
+
+        // pay 1% on 20.2 BTC, leaving 20 BTC for the curve
+        let info = mock_info(INVESTOR, &coins(2_020_202_020, DENOM));
+        let res = execute(deps.as_mut(), mock_env(), info, ExecuteMsg::Buy {}).unwrap();
+        assert_eq!(
+            res.messages,
+            vec![SubMsg::new(BankMsg::Send {
+                to_address: "dao".into(),
+                amount: coins(20_202_020, DENOM),
+            })]
+        );
+        assert_eq!(get_balance(deps.as_ref(), INVESTOR), Uint128::new(2000));
+
+        // burn 1000 EPOXY, releasing 15 BTC of which 2.5% goes to the dao
+        let info = mock_info(INVESTOR, &[]);
+        let burn = ExecuteMsg::Burn {
+            amount: Uint128::new(1000),
+        };
+        let res = execute(deps.as_mut(), mock_env(), info, burn).unwrap();
+        assert_eq!(
+            res.messages,
+            vec![
+                SubMsg::new(BankMsg::Send {
+                    to_address: INVESTOR.into(),
+                    amount: coins(1_462_500_000, DENOM),
+                }),
+                SubMsg::new(BankMsg::Send {
+                    to_address: "dao".into(),
+                    amount: coins(37_500_000, DENOM),
+                })
+            ]
+        );
+
+        // fees are not part of the reserve, but are tracked
+        let curve = query_curve_info(deps.as_ref(), curve_type.to_curve_fn()).unwrap();
+        assert_eq!(curve.reserve, Uint128::new(500_000_000));
+        assert_eq!(curve.supply, Uint128::new(1000));
+        assert_eq!(curve.buy_fee_bps, 100);
+        assert_eq!(curve.sell_fee_bps, 250);
+        assert_eq!(curve.fee_recipient, Some("dao".to_string()));
+        assert_eq!(curve.fees_collected, Uint128::new(57_702_020));
+    }
+
+    #[test]
+    fn fees_default_to_zero() {
+        let msg: InstantiateMsg = cosmwasm_std::from_slice(
+            br#"{"name":"Bonded","symbol":"EPOXY","decimals":6,"reserve_asset":{"native":{"denom":"satoshi"}},"reserve_decimals":6,"curve_type":{"constant":{"value":"1","scale":0}}}"#,
+        )
+        .unwrap();
+        assert_eq!(msg.buy_fee_bps, 0);
+        assert_eq!(msg.sell_fee_bps, 0);
+        assert_eq!(msg.fee_recipient, None);
+
+        let curve_fn = msg.curve_type.to_curve_fn();
+        let mut deps = mock_dependencies();
+        instantiate(deps.as_mut(), mock_env(), mock_info(CREATOR, &[]), msg).unwrap();
+        let curve = query_curve_info(deps.as_ref(), curve_fn).unwrap();
+        assert_eq!(curve.buy_fee_bps, 0);
+        assert_eq!(curve.sell_fee_bps, 0);
+    }
+
+    #[test]
+    fn invalid_fees_rejected() {
+        let mut deps = mock_dependencies();
+        let curve_type = CurveType::Constant {
+            value: Uint128::new(1),
+            scale: 0,
+        };
+
+        // fee must be below 100%
+        let mut msg = default_instantiate(6, 6, curve_type.clone());
+        msg.buy_fee_bps = 10_000;
+        msg.fee_recipient = Some("dao".to_string());
+        let err = instantiate(deps.as_mut(), mock_env(), mock_info(CREATOR, &[]), msg).unwrap_err();
+        assert_eq!(err, ContractError::InvalidFee { max: 10_000 });
+
+        // someone must receive the fees
+        let mut msg = default_instantiate(6, 6, curve_type);
+        msg.sell_fee_bps = 30;
+        let err = instantiate(deps.as_mut(), mock_env(), mock_info(CREATOR, &[]), msg).unwrap_err();
+        assert_eq!(err, ContractError::MissingFeeRecipient {});
+    }
+
+    #[test]
+    fn cw20_reserve_buy_and_sell() {
+        let mut deps = mock_dependencies();
+        let curve_type = CurveType::Linear {
+            slope: Uint128::new(1),
+            scale: 1,
+        };
+        let reserve_token = "stablecoin";
+        let mut msg = default_instantiate(2, 8, curve_type.clone());
+        msg.reserve_asset = ReserveAsset::Cw20 {
+            address: reserve_token.to_string(),
+        };
+        instantiate(deps.as_mut(), mock_env(), mock_info(CREATOR, &[]), msg).unwrap();
+
+        // native buy is not possible
+        let info = mock_info(INVESTOR, &coins(500_000_000, DENOM));
+        let err = execute(deps.as_mut(), mock_env(), info, ExecuteMsg::Buy {}).unwrap_err();
+        assert_eq!(err, ContractError::Cw20Reserve {});
+
+        // only the reserve token can be used to buy
+        let receive = ExecuteMsg::Receive(Cw20ReceiveMsg {
+            sender: INVESTOR.to_string(),
+            amount: Uint128::new(2_000_000_000),
+            msg: to_binary(&ReceiveMsg::Buy {}).unwrap(),
+        });
+        let info = mock_info("other-token", &[]);
+        let err = execute(deps.as_mut(), mock_env(), info, receive.clone()).unwrap_err();
+        assert_eq!(err, ContractError::Unauthorized {});
+
+        // buy credits the cw20 sender, not the token contract
+        let info = mock_info(reserve_token, &[]);
+        execute(deps.as_mut(), mock_env(), info, receive).unwrap();
+        assert_eq!(get_balance(deps.as_ref(), INVESTOR), Uint128::new(2000));
+        assert_eq!(get_balance(deps.as_ref(), reserve_token), Uint128::zero());
+
+        // selling pays out with a cw20 transfer
+        let info = mock_info(INVESTOR, &[]);
+        let burn = ExecuteMsg::Burn {
+            amount: Uint128::new(1000),
+        };
+        let res = execute(deps.as_mut(), mock_env(), info, burn).unwrap();
+        assert_eq!(
+            res.messages,
+            vec![SubMsg::new(WasmMsg::Execute {
+                contract_addr: reserve_token.into(),
+                msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                    recipient: INVESTOR.into(),
+                    amount: Uint128::new(1_500_000_000),
+                })
+                .unwrap(),
+                funds: vec![],
+            })]
+        );
+
+        let curve = query_curve_info(deps.as_ref(), curve_type.to_curve_fn()).unwrap();
+        assert_eq!(curve.reserve, Uint128::new(500_000_000));
+        assert_eq!(
+            curve.reserve_denom,
+            Denom::Cw20(Addr::unchecked(reserve_token))
+        );
+    }
+
+    #[test]
+    fn native_reserve_rejects_cw20() {
+        let mut deps = mock_dependencies();
+        let curve_type = CurveType::Constant {
+            value: Uint128::new(1),
+            scale: 0,
+        };
+        setup_test(deps.as_mut(), 6, 6, curve_type);
+
+        let receive = ExecuteMsg::Receive(Cw20ReceiveMsg {
+            sender: INVESTOR.to_string(),
+            amount: Uint128::new(1_000),
+            msg: to_binary(&ReceiveMsg::Buy {}).unwrap(),
+        });
+        let err = execute(deps.as_mut(), mock_env(), mock_info("token", &[]), receive).unwrap_err();
+        assert_eq!(err, ContractError::NativeReserve {});
+    }
+
+    #[test]
+    fn switch_curve_keeps_reserve() {
+        let mut deps = mock_dependencies();
+        let curve_type = CurveType::Linear {
+            slope: Uint128::new(1),
+            scale: 1,
+        };
+        setup_test(deps.as_mut(), 2, 8, curve_type);
+
+        // 20 BTC buys 2000 EPOXY
+        let info = mock_info(INVESTOR, &coins(2_000_000_000, DENOM));
+        execute(deps.as_mut(), mock_env(), info, ExecuteMsg::Buy {}).unwrap();
+
+        // only the admin can switch
+        let switch = ExecuteMsg::SwitchCurve {
+            curve_type: CurveType::Constant {
+                value: Uint128::new(5),
+                scale: 0,
+            },
+        };
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info(INVESTOR, &[]),
+            switch.clone(),
+        )
+        .unwrap_err();
+        assert_eq!(err, AdminError::NotAdmin {}.into());
+
+        // 20 EPOXY backed by 20 BTC means a constant price of 1, whatever value we pass in
+        execute(deps.as_mut(), mock_env(), mock_info(CREATOR, &[]), switch).unwrap();
+        let curve_type = CURVE_TYPE.load(&deps.storage).unwrap();
+        assert_eq!(
+            curve_type,
+            CurveType::Constant {
+                value: Uint128::new(1),
+                scale: 0,
+            }
+        );
+        let curve = query_curve_info(deps.as_ref(), curve_type.to_curve_fn()).unwrap();
+        assert_eq!(curve.reserve, Uint128::new(2_000_000_000));
+        assert_eq!(curve.supply, Uint128::new(2000));
+        assert_eq!(curve.spot_price, Decimal::one());
+
+        // a non-linear curve is scaled to at most the reserve we hold
+        let switch = ExecuteMsg::SwitchCurve {
+            curve_type: CurveType::SquareRoot {
+                slope: Uint128::new(1),
+                scale: 0,
+            },
+        };
+        execute(deps.as_mut(), mock_env(), mock_info(CREATOR, &[]), switch).unwrap();
+        let curve_type = CURVE_TYPE.load(&deps.storage).unwrap();
+        let reserve = curve_type.to_curve_fn()(DecimalPlaces::new(2, 8))
+            .reserve(Uint128::new(2000))
+            .unwrap();
+        assert!(reserve <= Uint128::new(2_000_000_000));
+        assert!(reserve >= Uint128::new(1_999_999_000));
+
+        // selling everything still works and releases the reserve
+        let info = mock_info(INVESTOR, &[]);
+        let burn = ExecuteMsg::Burn {
+            amount: Uint128::new(2000),
+        };
+        execute(deps.as_mut(), mock_env(), info, burn).unwrap();
+        let curve = query_curve_info(deps.as_ref(), curve_type.to_curve_fn()).unwrap();
+        assert_eq!(curve.supply, Uint128::zero());
+        assert_eq!(curve.reserve, Uint128::zero());
+    }
+
+    #[test]
+    fn migrate_legacy_state() {
+        let mut deps = mock_dependencies();
+        set_contract_version(&mut deps.storage, CONTRACT_NAME, "0.14.1").unwrap();
+        deps.storage.set(
+            b"curve_state",
+            br#"{"reserve":"500","supply":"1000","reserve_denom":"satoshi","decimals":{"supply":2,"reserve":8}}"#,
+        );
+
+        let msg = MigrateMsg {
+            admin: Some(CREATOR.to_string()),
+        };
+        migrate(deps.as_mut(), mock_env(), msg).unwrap();
+
+        let state = CURVE_STATE.load(&deps.storage).unwrap();
+        assert_eq!(state.reserve, Uint128::new(500));
+        assert_eq!(state.supply, Uint128::new(1000));
+        assert_eq!(state.reserve_denom, Denom::Native(DENOM.to_string()));
+        assert_eq!(state.fees_collected, Uint128::zero());
+        assert_eq!(
+            ADMIN.get(deps.as_ref()).unwrap(),
+            Some(Addr::unchecked(CREATOR))
+        );
+        let version = get_contract_version(&deps.storage).unwrap();
+        assert_eq!(version.version, CONTRACT_VERSION);
+        let raw = deps.storage.get(b"curve_state").unwrap();
+        assert!(String::from_utf8(raw)
+            .unwrap()
+            .contains(r#""reserve_denom":{"native":"satoshi"}"#));
+
+        // cannot migrate from another contract
+        set_contract_version(&mut deps.storage, "crates.io:cw20-base", "0.16.0").unwrap();
+        let err = migrate(deps.as_mut(), mock_env(), MigrateMsg { admin: None }).unwrap_err();
+        assert_eq!(
+            err,
+            ContractError::CannotMigrate {
+                previous_contract: "crates.io:cw20-base".to_string()
+            }
+        );
+
+        // cannot migrate from a newer version
+        set_contract_version(&mut deps.storage, CONTRACT_NAME, "9.0.0").unwrap();
+        let err = migrate(deps.as_mut(), mock_env(), MigrateMsg { admin: None }).unwrap_err();
+        assert_eq!(
+            err,
+            ContractError::CannotMigrateVersion {
+                previous_version: "9.0.0".to_string()
+            }
+        );
+    }
+
+    #[test]
+    fn hatch_then_curve() {
+        let mut deps = mock_dependencies();
+        let curve_type = CurveType::Linear {
+            slope: Uint128::new(1),
+            scale: 1,
+        };
+        let mut msg = default_instantiate(2, 8, curve_type);
+        // 0.5 BTC per EPOXY, 20% to the pool, raise at most 10 BTC, vest over 1000 seconds
+        msg.hatch = Some(HatchConfig {
+            allowlist: vec![INVESTOR.to_string()],
+            price: Uint128::new(5),
+            scale: 1,
+            funding_cap: Uint128::new(1_000_000_000),
+            funding_pool: "pool".to_string(),
+            funding_pool_bps: 2_000,
+            vesting_seconds: 1_000,
+        });
+        instantiate(deps.as_mut(), mock_env(), mock_info(CREATOR, &[]), msg).unwrap();
+        let curve = query_curve_info(
+            deps.as_ref(),
+            CURVE_TYPE.load(&deps.storage).unwrap().to_curve_fn(),
+        )
+        .unwrap();
+        assert_eq!(curve.phase, Phase::Hatch);
+
+        // only the allowlist may buy
+        let info = mock_info(BUYER, &coins(100_000_000, DENOM));
+        let err = execute(deps.as_mut(), mock_env(), info, ExecuteMsg::Buy {}).unwrap_err();
+        assert_eq!(err, ContractError::NotHatcher {});
+
+        // 4 BTC buys 8 EPOXY, 0.8 BTC goes to the pool, tokens are held by the contract
+        let info = mock_info(INVESTOR, &coins(400_000_000, DENOM));
+        let res = execute(deps.as_mut(), mock_env(), info, ExecuteMsg::Buy {}).unwrap();
+        assert_eq!(
+            res.messages,
+            vec![SubMsg::new(BankMsg::Send {
+                to_address: "pool".into(),
+                amount: coins(80_000_000, DENOM),
+            })]
+        );
+        assert_eq!(get_balance(deps.as_ref(), INVESTOR), Uint128::zero());
+        let contract = mock_env().contract.address;
+        assert_eq!(get_balance(deps.as_ref(), &contract), Uint128::new(800));
+
+        // no selling and no claiming during the hatch
+        let info = mock_info(INVESTOR, &[]);
+        let burn = ExecuteMsg::Burn {
+            amount: Uint128::new(1),
+        };
+        let err = execute(deps.as_mut(), mock_env(), info.clone(), burn).unwrap_err();
+        assert_eq!(err, ContractError::HatchActive {});
+        let err = execute(deps.as_mut(), mock_env(), info, ExecuteMsg::ClaimVested {}).unwrap_err();
+        assert_eq!(err, ContractError::NothingToClaim {});
+
+        // cannot go over the funding cap
+        let info = mock_info(INVESTOR, &coins(700_000_000, DENOM));
+        let err = execute(deps.as_mut(), mock_env(), info, ExecuteMsg::Buy {}).unwrap_err();
+        assert_eq!(
+            err,
+            ContractError::HatchCapExceeded {
+                remaining: Uint128::new(600_000_000)
+            }
+        );
+
+        // hitting the cap closes the hatch
+        let info = mock_info(INVESTOR, &coins(600_000_000, DENOM));
+        execute(deps.as_mut(), mock_env(), info, ExecuteMsg::Buy {}).unwrap();
+        let hatch = query_hatch(deps.as_ref()).unwrap();
+        assert_eq!(hatch.raised, Uint128::new(1_000_000_000));
+        assert_eq!(hatch.closed_at, Some(mock_env().block.time));
+
+        // the linear curve is fitted to 20 EPOXY backed by 8 BTC
+        let curve_type = CURVE_TYPE.load(&deps.storage).unwrap();
+        let curve = query_curve_info(deps.as_ref(), curve_type.to_curve_fn()).unwrap();
+        assert_eq!(curve.phase, Phase::Open);
+        assert_eq!(curve.supply, Uint128::new(2000));
+        assert_eq!(curve.reserve, Uint128::new(800_000_000));
+        assert_eq!(
+            curve_type,
+            CurveType::Linear {
+                slope: Uint128::new(4),
+                scale: 2
+            }
+        );
+
+        // anyone can buy on the curve now, 10 BTC more buys 20 EPOXY more
+        let info = mock_info(BUYER, &coins(1_000_000_000, DENOM));
+        execute(deps.as_mut(), mock_env(), info, ExecuteMsg::Buy {}).unwrap();
+        assert_eq!(get_balance(deps.as_ref(), BUYER), Uint128::new(1000));
+
+        // hatch tokens vest linearly
+        let mut env = mock_env();
+        env.block.time = env.block.time.plus_seconds(250);
+        let hatcher = query_hatcher(deps.as_ref(), env.clone(), INVESTOR.to_string()).unwrap();
+        assert_eq!(hatcher.contributed, Uint128::new(1_000_000_000));
+        assert_eq!(hatcher.tokens, Uint128::new(2000));
+        assert_eq!(hatcher.claimable, Uint128::new(500));
+        let info = mock_info(INVESTOR, &[]);
+        execute(
+            deps.as_mut(),
+            env.clone(),
+            info.clone(),
+            ExecuteMsg::ClaimVested {},
+        )
+        .unwrap();
+        assert_eq!(get_balance(deps.as_ref(), INVESTOR), Uint128::new(500));
+        let err = execute(
+            deps.as_mut(),
+            env.clone(),
+            info.clone(),
+            ExecuteMsg::ClaimVested {},
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::NothingToClaim {});
+
+        env.block.time = env.block.time.plus_seconds(5_000);
+        execute(deps.as_mut(), env, info, ExecuteMsg::ClaimVested {}).unwrap();
+        assert_eq!(get_balance(deps.as_ref(), INVESTOR), Uint128::new(2000));
+        assert_eq!(get_balance(deps.as_ref(), &contract), Uint128::zero());
+    }
+
+    #[test]
+    fn admin_closes_hatch() {
+        let mut deps = mock_dependencies();
+        let curve_type = CurveType::Constant {
+            value: Uint128::new(1),
+            scale: 0,
+        };
+        let mut msg = default_instantiate(6, 6, curve_type.clone());
+        msg.hatch = Some(HatchConfig {
+            allowlist: vec![INVESTOR.to_string()],
+            price: Uint128::new(1),
+            scale: 1,
+            funding_cap: Uint128::new(1_000_000),
+            funding_pool: "pool".to_string(),
+            funding_pool_bps: 0,
+            vesting_seconds: 0,
+        });
+        instantiate(deps.as_mut(), mock_env(), mock_info(CREATOR, &[]), msg).unwrap();
+
+        let info = mock_info(INVESTOR, &coins(1_000, DENOM));
+        execute(deps.as_mut(), mock_env(), info, ExecuteMsg::Buy {}).unwrap();
+
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info(INVESTOR, &[]),
+            ExecuteMsg::CloseHatch {},
+        )
+        .unwrap_err();
+        assert_eq!(err, AdminError::NotAdmin {}.into());
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info(CREATOR, &[]),
+            ExecuteMsg::CloseHatch {},
+        )
+        .unwrap();
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info(CREATOR, &[]),
+            ExecuteMsg::CloseHatch {},
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::HatchClosed {});
+
+        // the constant curve now prices at the hatch price, and everything is vested at once
+        let curve_type = CURVE_TYPE.load(&deps.storage).unwrap();
+        let curve = query_curve_info(deps.as_ref(), curve_type.to_curve_fn()).unwrap();
+        assert_eq!(curve.spot_price, Decimal::percent(10));
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info(INVESTOR, &[]),
+            ExecuteMsg::ClaimVested {},
+        )
+        .unwrap();
+        assert_eq!(get_balance(deps.as_ref(), INVESTOR), Uint128::new(10_000));
+    }
+
+    #[test]
+    fn hatch_closes_with_unfit_curve() {
+        let mut deps = mock_dependencies();
+        // this curve values any supply below 10^28 at zero, so it cannot be fit to the hatch
+        let curve_type = CurveType::Constant {
+            value: Uint128::new(1),
+            scale: 28,
+        };
+        let mut msg = default_instantiate(0, 0, curve_type.clone());
+        msg.hatch = Some(HatchConfig {
+            allowlist: vec![INVESTOR.to_string()],
+            price: Uint128::new(3),
+            scale: 0,
+            funding_cap: Uint128::new(1_000),
+            funding_pool: "pool".to_string(),
+            funding_pool_bps: 0,
+            vesting_seconds: 0,
+        });
+        instantiate(deps.as_mut(), mock_env(), mock_info(CREATOR, &[]), msg).unwrap();
+
+        // too little to buy a single token
+        let info = mock_info(INVESTOR, &coins(2, DENOM));
+        let err = execute(deps.as_mut(), mock_env(), info, ExecuteMsg::Buy {}).unwrap_err();
+        assert_eq!(err, cw20_base::ContractError::InvalidZeroAmount {}.into());
+        assert_eq!(query_hatch(deps.as_ref()).unwrap().raised, Uint128::zero());
+
+        let info = mock_info(INVESTOR, &coins(300, DENOM));
+        execute(deps.as_mut(), mock_env(), info, ExecuteMsg::Buy {}).unwrap();
+
+        // the hatch still closes and keeps the configured curve
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info(CREATOR, &[]),
+            ExecuteMsg::CloseHatch {},
+        )
+        .unwrap();
+        assert_eq!(CURVE_TYPE.load(&deps.storage).unwrap(), curve_type);
+        let curve = query_curve_info(deps.as_ref(), curve_type.to_curve_fn()).unwrap();
+        assert_eq!(curve.phase, Phase::Open);
+        assert_eq!(curve.supply, Uint128::new(100));
+    }
+
     #[test]
     fn cw20_imports_work() {
         let mut deps = mock_dependencies();
@@ -633,4 +1839,313 @@ mod tests {
             })
         );
     }
+
+    #[test]
+    fn marketing_and_enumerable_queries() {
+        let mut deps = mock_dependencies();
+        let curve_type = CurveType::Constant {
+            value: Uint128::new(15),
+            scale: 1,
+        };
+        let mut msg = default_instantiate(2, 8, curve_type);
+        msg.marketing = Some(InstantiateMarketingInfo {
+            project: Some("Epoxy".to_string()),
+            description: None,
+            marketing: Some(CREATOR.to_string()),
+            logo: Some(Logo::Url("https://epoxy.example/logo.png".to_string())),
+        });
+
+        // logos are checked at instantiate like on upload
+        let mut invalid = msg.clone();
+        invalid.marketing.as_mut().unwrap().logo = Some(Logo::Embedded(EmbeddedLogo::Png(
+            Binary::from(b"not a png".to_vec()),
+        )));
+        let err =
+            instantiate(deps.as_mut(), mock_env(), mock_info(CREATOR, &[]), invalid).unwrap_err();
+        assert_eq!(
+            err,
+            ContractError::Base(cw20_base::ContractError::InvalidPngHeader {})
+        );
+
+        instantiate(deps.as_mut(), mock_env(), mock_info(CREATOR, &[]), msg).unwrap();
+
+        let marketing = query_marketing_info(deps.as_ref()).unwrap();
+        assert_eq!(marketing.project, Some("Epoxy".to_string()));
+        assert_eq!(marketing.marketing, Some(Addr::unchecked(CREATOR)));
+        assert_eq!(
+            marketing.logo,
+            Some(LogoInfo::Url("https://epoxy.example/logo.png".to_string()))
+        );
+
+        // only the marketing account may update it
+        let update = ExecuteMsg::UpdateMarketing {
+            project: None,
+            description: Some("Bonded epoxy".to_string()),
+            marketing: None,
+        };
+        let info = mock_info(INVESTOR, &[]);
+        let err = execute(deps.as_mut(), mock_env(), info, update.clone()).unwrap_err();
+        assert_eq!(
+            err,
+            ContractError::Base(cw20_base::ContractError::Unauthorized {})
+        );
+        let info = mock_info(CREATOR, &[]);
+        execute(deps.as_mut(), mock_env(), info, update).unwrap();
+
+        // an embedded logo is stored and can be downloaded
+        let png = Binary::from(b"\x89PNG\r\n\x1a\nfake".to_vec());
+        let upload = ExecuteMsg::UploadLogo(Logo::Embedded(EmbeddedLogo::Png(png.clone())));
+        let info = mock_info(CREATOR, &[]);
+        execute(deps.as_mut(), mock_env(), info, upload).unwrap();
+        let marketing = query_marketing_info(deps.as_ref()).unwrap();
+        assert_eq!(marketing.description, Some("Bonded epoxy".to_string()));
+        assert_eq!(marketing.logo, Some(LogoInfo::Embedded));
+        let logo = query_download_logo(deps.as_ref()).unwrap();
+        assert_eq!(logo.mime_type, "image/png");
+        assert_eq!(logo.data, png);
+
+        // buy some tokens and approve a spender to see them listed
+        let info = mock_info(INVESTOR, &coins(40_000_000, DENOM));
+        execute(deps.as_mut(), mock_env(), info, ExecuteMsg::Buy {}).unwrap();
+        let info = mock_info(BUYER, &coins(10_000_000, DENOM));
+        execute(deps.as_mut(), mock_env(), info, ExecuteMsg::Buy {}).unwrap();
+        let info = mock_info(INVESTOR, &[]);
+        let approve = ExecuteMsg::IncreaseAllowance {
+            spender: BUYER.to_string(),
+            amount: Uint128::new(100),
+            expires: None,
+        };
+        execute(deps.as_mut(), mock_env(), info, approve).unwrap();
+
+        let msg = QueryMsg::AllAccounts {
+            start_after: None,
+            limit: None,
+        };
+        let accounts: AllAccountsResponse =
+            from_binary(&query(deps.as_ref(), mock_env(), msg).unwrap()).unwrap();
+        assert_eq!(
+            accounts.accounts,
+            vec![BUYER.to_string(), INVESTOR.to_string()]
+        );
+
+        let msg = QueryMsg::AllAllowances {
+            owner: INVESTOR.to_string(),
+            start_after: None,
+            limit: None,
+        };
+        let allowances: AllAllowancesResponse =
+            from_binary(&query(deps.as_ref(), mock_env(), msg).unwrap()).unwrap();
+        assert_eq!(allowances.allowances.len(), 1);
+        assert_eq!(allowances.allowances[0].spender, BUYER);
+
+        let msg = QueryMsg::AllSpenderAllowances {
+            spender: BUYER.to_string(),
+            start_after: None,
+            limit: None,
+        };
+        let allowances: AllSpenderAllowancesResponse =
+            from_binary(&query(deps.as_ref(), mock_env(), msg).unwrap()).unwrap();
+        assert_eq!(allowances.allowances.len(), 1);
+        assert_eq!(allowances.allowances[0].owner, INVESTOR);
+        assert_eq!(allowances.allowances[0].allowance, Uint128::new(100));
+    }
+
+    #[test]
+    fn supply_cap_refunds_excess() {
+        let mut deps = mock_dependencies();
+        let curve_type = CurveType::Constant {
+            value: Uint128::new(15),
+            scale: 1,
+        };
+        let mut msg = default_instantiate(2, 8, curve_type.clone());
+        msg.max_supply = Some(Uint128::zero());
+        let err = instantiate(deps.as_mut(), mock_env(), mock_info(CREATOR, &[]), msg).unwrap_err();
+        assert_eq!(err, ContractError::InvalidLimit {});
+
+        let mut msg = default_instantiate(2, 8, curve_type.clone());
+        msg.max_supply = Some(Uint128::new(20));
+        msg.buy_fee_bps = 100;
+        msg.fee_recipient = Some(CREATOR.to_string());
+        instantiate(deps.as_mut(), mock_env(), mock_info(CREATOR, &[]), msg).unwrap();
+
+        // 40_000_000 less 1% fee would buy 26 tokens, but only 20 fit under the cap.
+        // those cost 30_000_000 plus a proportional fee, the rest comes back
+        let info = mock_info(INVESTOR, &coins(40_000_000, DENOM));
+        let res = execute(deps.as_mut(), mock_env(), info, ExecuteMsg::Buy {}).unwrap();
+        assert_eq!(get_balance(deps.as_ref(), INVESTOR), Uint128::new(20));
+        assert_eq!(
+            res.messages,
+            vec![
+                SubMsg::new(BankMsg::Send {
+                    to_address: INVESTOR.into(),
+                    amount: coins(9_696_970, DENOM),
+                }),
+                SubMsg::new(BankMsg::Send {
+                    to_address: CREATOR.into(),
+                    amount: coins(303_030, DENOM),
+                }),
+            ]
+        );
+
+        let curve = query_curve_info(deps.as_ref(), curve_type.to_curve_fn()).unwrap();
+        assert_eq!(curve.reserve, Uint128::new(30_000_000));
+        assert_eq!(curve.supply, Uint128::new(20));
+        assert_eq!(curve.max_supply, Some(Uint128::new(20)));
+
+        // nothing left to buy
+        let info = mock_info(BUYER, &coins(1_500_000, DENOM));
+        let err = execute(deps.as_mut(), mock_env(), info, ExecuteMsg::Buy {}).unwrap_err();
+        assert_eq!(err, ContractError::BuyLimitReached {});
+
+        // selling makes room again
+        let info = mock_info(INVESTOR, &[]);
+        let burn = ExecuteMsg::Burn {
+            amount: Uint128::new(5),
+        };
+        execute(deps.as_mut(), mock_env(), info, burn).unwrap();
+        let info = mock_info(BUYER, &coins(3_000_000, DENOM));
+        execute(deps.as_mut(), mock_env(), info, ExecuteMsg::Buy {}).unwrap();
+        assert_eq!(get_balance(deps.as_ref(), BUYER), Uint128::new(1));
+    }
+
+    #[test]
+    fn buy_limits_refund_excess() {
+        let mut deps = mock_dependencies();
+        let curve_type = CurveType::Constant {
+            value: Uint128::new(15),
+            scale: 1,
+        };
+        let mut msg = default_instantiate(2, 8, curve_type);
+        msg.max_buy = Some(Uint128::new(15));
+        msg.max_buy_per_address = Some(Uint128::new(25));
+        instantiate(deps.as_mut(), mock_env(), mock_info(CREATOR, &[]), msg).unwrap();
+
+        // one transaction only gets 15 tokens, costing 22_500_000
+        let info = mock_info(INVESTOR, &coins(40_000_000, DENOM));
+        let res = execute(deps.as_mut(), mock_env(), info, ExecuteMsg::Buy {}).unwrap();
+        assert_eq!(get_balance(deps.as_ref(), INVESTOR), Uint128::new(15));
+        assert_eq!(
+            res.messages,
+            vec![SubMsg::new(BankMsg::Send {
+                to_address: INVESTOR.into(),
+                amount: coins(17_500_000, DENOM),
+            })]
+        );
+
+        // the next one is limited by the 10 tokens left for this address
+        let info = mock_info(INVESTOR, &coins(40_000_000, DENOM));
+        execute(deps.as_mut(), mock_env(), info, ExecuteMsg::Buy {}).unwrap();
+        assert_eq!(get_balance(deps.as_ref(), INVESTOR), Uint128::new(25));
+
+        // transferring tokens away does not reset the limit
+        let info = mock_info(INVESTOR, &[]);
+        let transfer = ExecuteMsg::Transfer {
+            recipient: BUYER.into(),
+            amount: Uint128::new(25),
+        };
+        execute(deps.as_mut(), mock_env(), info, transfer).unwrap();
+        let info = mock_info(INVESTOR, &coins(1_500_000, DENOM));
+        let err = execute(deps.as_mut(), mock_env(), info, ExecuteMsg::Buy {}).unwrap_err();
+        assert_eq!(err, ContractError::BuyLimitReached {});
+
+        // other addresses have their own limit
+        let info = mock_info(BUYER, &coins(1_500_000, DENOM));
+        execute(deps.as_mut(), mock_env(), info, ExecuteMsg::Buy {}).unwrap();
+        assert_eq!(get_balance(deps.as_ref(), BUYER), Uint128::new(26));
+    }
+
+    #[test]
+    fn buy_for_and_buy_and_send() {
+        let mut deps = mock_dependencies();
+        let curve_type = CurveType::Constant {
+            value: Uint128::new(15),
+            scale: 1,
+        };
+        let msg = default_instantiate(2, 8, curve_type);
+        instantiate(deps.as_mut(), mock_env(), mock_info(CREATOR, &[]), msg).unwrap();
+
+        // the router pays, the user gets the tokens
+        let info = mock_info(INVESTOR, &coins(15_000_000, DENOM));
+        let buy_for = ExecuteMsg::BuyFor {
+            recipient: BUYER.to_string(),
+        };
+        execute(deps.as_mut(), mock_env(), info, buy_for).unwrap();
+        assert_eq!(get_balance(deps.as_ref(), INVESTOR), Uint128::zero());
+        assert_eq!(get_balance(deps.as_ref(), BUYER), Uint128::new(10));
+
+        // minted straight into a staking contract, which is told who bought them
+        let staking = "staking";
+        let hook = Binary::from(br#"{"stake":{}}"#.to_vec());
+        let info = mock_info(INVESTOR, &coins(30_000_000, DENOM));
+        let buy_and_send = ExecuteMsg::BuyAndSend {
+            contract: staking.to_string(),
+            msg: hook.clone(),
+        };
+        let res = execute(deps.as_mut(), mock_env(), info, buy_and_send).unwrap();
+        assert_eq!(get_balance(deps.as_ref(), INVESTOR), Uint128::zero());
+        assert_eq!(get_balance(deps.as_ref(), staking), Uint128::new(20));
+        assert_eq!(
+            res.messages,
+            vec![SubMsg::new(
+                Cw20ReceiveMsg {
+                    sender: INVESTOR.to_string(),
+                    amount: Uint128::new(20),
+                    msg: hook,
+                }
+                .into_cosmos_msg(staking)
+                .unwrap()
+            )]
+        );
+    }
+
+    #[test]
+    fn cw20_reserve_buy_for_and_hatch() {
+        let mut deps = mock_dependencies();
+        let curve_type = CurveType::Constant {
+            value: Uint128::new(15),
+            scale: 1,
+        };
+        let mut msg = default_instantiate(2, 8, curve_type);
+        msg.reserve_asset = ReserveAsset::Cw20 {
+            address: "reserve".to_string(),
+        };
+        msg.hatch = Some(HatchConfig {
+            allowlist: vec![BUYER.to_string()],
+            price: Uint128::new(1),
+            scale: 0,
+            funding_cap: Uint128::new(100_000_000),
+            funding_pool: CREATOR.to_string(),
+            funding_pool_bps: 0,
+            vesting_seconds: 0,
+        });
+        instantiate(deps.as_mut(), mock_env(), mock_info(CREATOR, &[]), msg).unwrap();
+
+        // hatch tokens cannot be sent on
+        let receive = ExecuteMsg::Receive(Cw20ReceiveMsg {
+            sender: INVESTOR.to_string(),
+            amount: Uint128::new(1_000_000),
+            msg: to_binary(&ReceiveMsg::BuyAndSend {
+                contract: "staking".to_string(),
+                msg: Binary::default(),
+            })
+            .unwrap(),
+        });
+        let info = mock_info("reserve", &[]);
+        let err = execute(deps.as_mut(), mock_env(), info, receive).unwrap_err();
+        assert_eq!(err, ContractError::HatchActive {});
+
+        // but anyone can contribute on behalf of a hatcher
+        let receive = ExecuteMsg::Receive(Cw20ReceiveMsg {
+            sender: INVESTOR.to_string(),
+            amount: Uint128::new(1_000_000),
+            msg: to_binary(&ReceiveMsg::BuyFor {
+                recipient: BUYER.to_string(),
+            })
+            .unwrap(),
+        });
+        let info = mock_info("reserve", &[]);
+        execute(deps.as_mut(), mock_env(), info, receive).unwrap();
+        let hatcher = query_hatcher(deps.as_ref(), mock_env(), BUYER.to_string()).unwrap();
+        assert_eq!(hatcher.contributed, Uint128::new(1_000_000));
+    }
 }
@@ -1,11 +1,11 @@
 use cosmwasm_schema::cw_serde;
-use integer_cbrt::IntegerCubeRoot;
-use integer_sqrt::IntegerSquareRoot;
 use rust_decimal::prelude::ToPrimitive;
 use rust_decimal::Decimal;
-use std::str::FromStr;
+use std::convert::TryFrom;
 
-use cosmwasm_std::{Decimal as StdDecimal, Uint128};
+use cosmwasm_std::{Decimal as StdDecimal, Uint128, Uint512};
+
+use crate::ContractError;
 
 /// This defines the curves we are using.
 ///
@@ -19,44 +19,84 @@ use cosmwasm_std::{Decimal as StdDecimal, Uint128};
 /// when you want to do more complex math than add and multiply `Uint128`, I decided to go the second
 /// route. That made the signatures quite complex and my final idea was to pass in `supply_decimal`
 /// and `reserve_decimal` in the curve constructors.
+///
+/// The curves below keep their parameters as `Decimal`, but do the math itself on integers in
+/// Uint512, rounding down at every step. Uint256 is not enough: squaring a Uint128 supply that
+/// has been scaled up to 18 decimal places already overflows it. `Decimal` runs out of decimal
+/// places for small normalized amounts and rounds half up, which could let someone sell back
+/// for more than they paid.
+/// All methods return an error rather than panic if the numbers get too big for the math.
 pub trait Curve {
     /// Returns the spot price given the supply.
     /// `f(x)` from the README
-    fn spot_price(&self, supply: Uint128) -> StdDecimal;
+    fn spot_price(&self, supply: Uint128) -> Result<StdDecimal, ContractError>;
 
     /// Returns the total price paid up to purchase supply tokens (integral)
     /// `F(x)` from the README
-    fn reserve(&self, supply: Uint128) -> Uint128;
+    fn reserve(&self, supply: Uint128) -> Result<Uint128, ContractError>;
 
     /// Inverse of reserve. Returns how many tokens would be issued
     /// with a total paid amount of reserve.
     /// `F^-1(x)` from the README
-    fn supply(&self, reserve: Uint128) -> Uint128;
+    fn supply(&self, reserve: Uint128) -> Result<Uint128, ContractError>;
 }
 
 /// decimal returns an object = num * 10 ^ -scale
 /// We use this function in contract.rs rather than call the crate constructor
 /// itself, in case we want to swap out the implementation, we can do it only in this file.
+///
+/// Panics if the value cannot be represented, use `checked_decimal` for untrusted input.
 pub fn decimal<T: Into<u128>>(num: T, scale: u32) -> Decimal {
-    Decimal::from_i128_with_scale(num.into() as i128, scale)
+    checked_decimal(num, scale).unwrap()
 }
 
-/// StdDecimal stores as a u128 with 18 decimal points of precision
-fn decimal_to_std(x: Decimal) -> StdDecimal {
-    // this seems straight-forward (if inefficient), converting via string representation
-    // TODO: execute errors better? Result?
-    StdDecimal::from_str(&x.to_string()).unwrap()
-
-    // // maybe a better approach doing math, not sure about rounding
-    //
-    // // try to preserve decimal points, max 9
-    // let digits = min(x.scale(), 9);
-    // let multiplier = 10u128.pow(digits);
-    //
-    // // we multiply up before we round off to u128,
-    // // let StdDecimal do its best to keep these decimal places
-    // let nominator = (x * decimal(multiplier, 0)).to_u128().unwrap();
-    // StdDecimal::from_ratio(nominator, multiplier)
+/// Most decimal places a `Decimal` can hold
+const MAX_SCALE: u32 = 28;
+
+/// checked_decimal returns num * 10 ^ -scale, or an error if num has more than 96 bits
+/// or scale is more than 28
+pub fn checked_decimal<T: Into<u128>>(num: T, scale: u32) -> Result<Decimal, ContractError> {
+    let num = num.into();
+    i128::try_from(num)
+        .ok()
+        .and_then(|n| Decimal::try_from_i128_with_scale(n, scale).ok())
+        .ok_or(ContractError::DecimalOverflow {
+            amount: Uint128::new(num),
+            decimals: scale,
+        })
+}
+
+/// scale_decimal multiplies `num * 10 ^ -scale` by `numerator / denominator` and returns
+/// the result in the same `(num, scale)` form, or None if it cannot be represented.
+/// The result keeps as many decimal places as a `Decimal` can hold and is rounded down,
+/// so a rescaled curve never values the supply at more than `numerator`.
+pub fn scale_decimal(
+    num: Uint128,
+    scale: u32,
+    numerator: Uint128,
+    denominator: Uint128,
+) -> Option<(Uint128, u32)> {
+    if denominator.is_zero() || scale > MAX_SCALE {
+        return None;
+    }
+    let product = Uint512::from(num) * Uint512::from(numerator);
+    let denominator = Uint512::from(denominator);
+    let max_mantissa = Uint512::from(1u128 << 96);
+    // add the most decimal places that still fit in the 96 bit mantissa of a Decimal
+    let (mut mantissa, mut scale) = (scale..=MAX_SCALE).rev().find_map(|new_scale| {
+        let places = Uint512::from(10u8).pow(new_scale - scale);
+        let mantissa = product * places / denominator;
+        (mantissa < max_mantissa).then_some((mantissa, new_scale))
+    })?;
+    if mantissa.is_zero() {
+        return None;
+    }
+    let ten = Uint512::from(10u8);
+    while scale > 0 && (mantissa % ten).is_zero() {
+        mantissa /= ten;
+        scale -= 1;
+    }
+    Some((Uint128::try_from(mantissa).ok()?, scale))
 }
 
 /// spot price is always a constant value
@@ -74,23 +114,28 @@ impl Constant {
 impl Curve for Constant {
     // we need to normalize value with the reserve decimal places
     // (eg 0.1 value would return 100_000 if reserve was uatom)
-    fn spot_price(&self, _supply: Uint128) -> StdDecimal {
+    fn spot_price(&self, _supply: Uint128) -> Result<StdDecimal, ContractError> {
         // f(x) = self.value
-        decimal_to_std(self.value)
+        let (value, value_places) = decimal_parts(self.value)?;
+        to_price(value, -(value_places as i64))
     }
 
     /// Returns total number of reserve tokens needed to purchase a given number of supply tokens.
     /// Note that both need to be normalized.
-    fn reserve(&self, supply: Uint128) -> Uint128 {
+    fn reserve(&self, supply: Uint128) -> Result<Uint128, ContractError> {
         // f(x) = supply * self.value
-        let reserve = self.normalize.from_supply(supply) * self.value;
-        self.normalize.clone().to_reserve(reserve)
+        let (value, value_places) = decimal_parts(self.value)?;
+        let places = &self.normalize;
+        let exp = places.reserve as i64 - value_places as i64 - places.supply as i64;
+        floor_ratio(mul512(supply.into(), value)?, exp, Uint512::one())
     }
 
-    fn supply(&self, reserve: Uint128) -> Uint128 {
+    fn supply(&self, reserve: Uint128) -> Result<Uint128, ContractError> {
         // f(x) = reserve / self.value
-        let supply = self.normalize.from_reserve(reserve) / self.value;
-        self.normalize.clone().to_supply(supply)
+        let (value, value_places) = decimal_parts(self.value)?;
+        let places = &self.normalize;
+        let exp = value_places as i64 + places.supply as i64 - places.reserve as i64;
+        floor_ratio(reserve.into(), exp, value)
     }
 }
 
@@ -107,27 +152,34 @@ impl Linear {
 }
 
 impl Curve for Linear {
-    fn spot_price(&self, supply: Uint128) -> StdDecimal {
+    fn spot_price(&self, supply: Uint128) -> Result<StdDecimal, ContractError> {
         // f(x) = supply * self.value
-        let out = self.normalize.from_supply(supply) * self.slope;
-        decimal_to_std(out)
+        let (slope, slope_places) = decimal_parts(self.slope)?;
+        let exp = -(slope_places as i64) - self.normalize.supply as i64;
+        to_price(mul512(supply.into(), slope)?, exp)
     }
 
-    fn reserve(&self, supply: Uint128) -> Uint128 {
+    fn reserve(&self, supply: Uint128) -> Result<Uint128, ContractError> {
         // f(x) = self.slope * supply * supply / 2
-        let normalized = self.normalize.from_supply(supply);
-        let square = normalized * normalized;
-        // Note: multiplying by 0.5 is much faster than dividing by 2
-        let reserve = square * self.slope * Decimal::new(5, 1);
-        self.normalize.clone().to_reserve(reserve)
+        let (slope, slope_places) = decimal_parts(self.slope)?;
+        let places = &self.normalize;
+        let supply = Uint512::from(supply);
+        let square = mul512(supply, supply)?;
+        let exp = places.reserve as i64 - slope_places as i64 - 2 * places.supply as i64;
+        floor_ratio(mul512(square, slope)?, exp, Uint512::from(2u8))
     }
 
-    fn supply(&self, reserve: Uint128) -> Uint128 {
+    fn supply(&self, reserve: Uint128) -> Result<Uint128, ContractError> {
         // f(x) = (2 * reserve / self.slope) ^ 0.5
         // note: use addition here to optimize 2* operation
-        let square = self.normalize.from_reserve(reserve + reserve) / self.slope;
-        let supply = square_root(square);
-        self.normalize.clone().to_supply(supply)
+        let (slope, slope_places) = decimal_parts(self.slope)?;
+        let places = &self.normalize;
+        let reserve = Uint512::from(reserve);
+        let exp = slope_places as i64 + 2 * places.supply as i64 - places.reserve as i64;
+        let square = shift10(reserve + reserve, exp)?
+            .checked_div(slope)
+            .map_err(|_| ContractError::CurveOverflow {})?;
+        Uint128::try_from(isqrt(square)).map_err(|_| ContractError::AmountOverflow {})
     }
 }
 
@@ -144,60 +196,135 @@ impl SquareRoot {
 }
 
 impl Curve for SquareRoot {
-    fn spot_price(&self, supply: Uint128) -> StdDecimal {
+    fn spot_price(&self, supply: Uint128) -> Result<StdDecimal, ContractError> {
         // f(x) = self.slope * supply^0.5
-        let square = self.normalize.from_supply(supply);
-        let root = square_root(square);
-        decimal_to_std(root * self.slope)
+        // sqrt(x) * 10^18 = sqrt(supply * 10^(36 - supply_places))
+        let (slope, slope_places) = decimal_parts(self.slope)?;
+        let root = isqrt(shift10(supply.into(), 36 - self.normalize.supply as i64)?);
+        to_price(mul512(slope, root)?, -(slope_places as i64) - 18)
     }
 
-    fn reserve(&self, supply: Uint128) -> Uint128 {
+    fn reserve(&self, supply: Uint128) -> Result<Uint128, ContractError> {
         // f(x) = self.slope * supply * supply^0.5 / 1.5
-        let normalized = self.normalize.from_supply(supply);
-        let root = square_root(normalized);
-        let reserve = self.slope * normalized * root / Decimal::new(15, 1);
-        self.normalize.clone().to_reserve(reserve)
+        // with root = sqrt(supply * 10^(2 * ROOT_PLACES - supply_places)), this is
+        // 2 * slope * supply * root / 3
+        //   * 10^(reserve_places - slope_places - supply_places - ROOT_PLACES)
+        let (slope, slope_places) = decimal_parts(self.slope)?;
+        let places = &self.normalize;
+        let root = isqrt(shift10(
+            supply.into(),
+            2 * ROOT_PLACES as i64 - places.supply as i64,
+        )?);
+        let product = mul512(mul512(slope + slope, supply.into())?, root)?;
+        let exp =
+            places.reserve as i64 - slope_places as i64 - places.supply as i64 - ROOT_PLACES as i64;
+        let reserve = shift10(product, exp)? / Uint512::from(3u8);
+        Uint128::try_from(reserve).map_err(|_| ContractError::AmountOverflow {})
     }
 
-    fn supply(&self, reserve: Uint128) -> Uint128 {
+    fn supply(&self, reserve: Uint128) -> Result<Uint128, ContractError> {
         // f(x) = (1.5 * reserve / self.slope) ^ (2/3)
-        let base = self.normalize.from_reserve(reserve) * Decimal::new(15, 1) / self.slope;
-        let squared = base * base;
-        let supply = cube_root(squared);
-        self.normalize.clone().to_supply(supply)
+        // which is the cube root of
+        // 9 * reserve^2 / (4 * slope^2)
+        //   * 10^(2 * slope_places + 3 * supply_places - 2 * reserve_places)
+        let (slope, slope_places) = decimal_parts(self.slope)?;
+        let places = &self.normalize;
+        let reserve = Uint512::from(reserve);
+        let numerator = mul512(mul512(reserve, reserve)?, Uint512::from(9u8))?;
+        let exp = 2 * slope_places as i64 + 3 * places.supply as i64 - 2 * places.reserve as i64;
+        let denominator = mul512(mul512(slope, slope)?, Uint512::from(4u8))?;
+        let cube = shift10(numerator, exp)?
+            .checked_div(denominator)
+            .map_err(|_| ContractError::CurveOverflow {})?;
+        Uint128::try_from(icbrt(cube)).map_err(|_| ContractError::AmountOverflow {})
     }
 }
 
-// we multiply by 10^18, turn to int, take square root, then divide by 10^9 as we convert back to decimal
-fn square_root(square: Decimal) -> Decimal {
-    // must be even
-    // TODO: this can overflow easily at 18... what is a good value?
-    const EXTRA_DIGITS: u32 = 12;
-    let multiplier = 10u128.saturating_pow(EXTRA_DIGITS);
+/// Decimal places of the roots taken in `SquareRoot`
+const ROOT_PLACES: u32 = 18;
+
+/// Splits a non-negative decimal into `(mantissa, scale)` so that it is `mantissa * 10^-scale`
+fn decimal_parts(value: Decimal) -> Result<(Uint512, u32), ContractError> {
+    let mantissa = value
+        .mantissa()
+        .to_u128()
+        .ok_or(ContractError::CurveOverflow {})?;
+    Ok((Uint512::from(mantissa), value.scale()))
+}
+
+fn mul512(a: Uint512, b: Uint512) -> Result<Uint512, ContractError> {
+    a.checked_mul(b)
+        .map_err(|_| ContractError::CurveOverflow {})
+}
 
-    // multiply by 10^18 and turn to u128
-    let extended = square * decimal(multiplier, 0);
-    let extended = extended.floor().to_u128().unwrap();
+/// Returns `num * 10^exp / den`, rounded down
+fn floor_ratio(num: Uint512, exp: i64, den: Uint512) -> Result<Uint128, ContractError> {
+    let out = shift10(num, exp)?
+        .checked_div(den)
+        .map_err(|_| ContractError::CurveOverflow {})?;
+    Uint128::try_from(out).map_err(|_| ContractError::AmountOverflow {})
+}
 
-    // take square root, and build a decimal again
-    let root = extended.integer_sqrt();
-    decimal(root, EXTRA_DIGITS / 2)
+/// Returns `num * 10^exp` as a Decimal with 18 decimal places, rounded down
+fn to_price(num: Uint512, exp: i64) -> Result<StdDecimal, ContractError> {
+    let atomics = shift10(num, exp + 18)?;
+    let atomics = Uint128::try_from(atomics).map_err(|_| ContractError::PriceOverflow {})?;
+    Ok(StdDecimal::new(atomics))
 }
 
-// we multiply by 10^9, turn to int, take cube root, then divide by 10^3 as we convert back to decimal
-fn cube_root(cube: Decimal) -> Decimal {
-    // must be multiple of 3
-    // TODO: what is a good value?
-    const EXTRA_DIGITS: u32 = 9;
-    let multiplier = 10u128.saturating_pow(EXTRA_DIGITS);
+/// Returns `num * 10^exp`, rounded down if exp is negative
+fn shift10(num: Uint512, exp: i64) -> Result<Uint512, ContractError> {
+    let factor = u32::try_from(exp.unsigned_abs())
+        .ok()
+        .and_then(|exp| Uint512::from(10u8).checked_pow(exp).ok());
+    match factor {
+        Some(factor) if exp >= 0 => mul512(num, factor),
+        Some(factor) => Ok(num / factor),
+        // dividing by more than fits in a Uint512 leaves nothing
+        None if exp < 0 => Ok(Uint512::zero()),
+        None => Err(ContractError::CurveOverflow {}),
+    }
+}
 
-    // multiply out and turn to u128
-    let extended = cube * decimal(multiplier, 0);
-    let extended = extended.floor().to_u128().unwrap();
+/// Number of bits needed to represent n
+fn bit_length(n: Uint512) -> u32 {
+    let bytes = n.to_be_bytes();
+    match bytes.iter().position(|b| *b != 0) {
+        Some(i) => (bytes.len() - i) as u32 * 8 - bytes[i].leading_zeros(),
+        None => 0,
+    }
+}
 
-    // take cube root, and build a decimal again
-    let root = extended.integer_cbrt();
-    decimal(root, EXTRA_DIGITS / 3)
+/// Largest r with r^2 <= n, using Newton's method
+fn isqrt(n: Uint512) -> Uint512 {
+    if n.is_zero() {
+        return n;
+    }
+    // start at a power of two above the root, from there Newton's method
+    // decreases monotonically until it hits the floor of the root
+    let mut x = Uint512::from(2u8).pow((bit_length(n) + 1) / 2);
+    loop {
+        let y = (x + n / x) >> 1;
+        if y >= x {
+            return x;
+        }
+        x = y;
+    }
+}
+
+/// Largest r with r^3 <= n, using Newton's method
+fn icbrt(n: Uint512) -> Uint512 {
+    if n.is_zero() {
+        return n;
+    }
+    let mut x = Uint512::from(2u8).pow((bit_length(n) + 2) / 3);
+    loop {
+        let y = (x + x + n / (x * x)) / Uint512::from(3u8);
+        if y >= x {
+            return x;
+        }
+        x = y;
+    }
 }
 
 /// DecimalPlaces should be passed into curve constructors
@@ -217,32 +344,44 @@ impl DecimalPlaces {
         }
     }
 
-    pub fn to_reserve(self, reserve: Decimal) -> Uint128 {
-        let factor = decimal(10u128.pow(self.reserve), 0);
-        let out = reserve * factor;
-        // TODO: execute overflow better? Result?
-        out.floor().to_u128().unwrap().into()
+    pub fn to_reserve(self, reserve: Decimal) -> Result<Uint128, ContractError> {
+        to_uint(reserve, self.reserve)
     }
 
-    pub fn to_supply(self, supply: Decimal) -> Uint128 {
-        let factor = decimal(10u128.pow(self.supply), 0);
-        let out = supply * factor;
-        // TODO: execute overflow better? Result?
-        out.floor().to_u128().unwrap().into()
+    pub fn to_supply(self, supply: Decimal) -> Result<Uint128, ContractError> {
+        to_uint(supply, self.supply)
     }
 
-    pub fn from_supply(&self, supply: Uint128) -> Decimal {
-        decimal(supply, self.supply)
+    pub fn from_supply(&self, supply: Uint128) -> Result<Decimal, ContractError> {
+        checked_decimal(supply, self.supply)
     }
 
-    pub fn from_reserve(&self, reserve: Uint128) -> Decimal {
-        decimal(reserve, self.reserve)
+    pub fn from_reserve(&self, reserve: Uint128) -> Result<Decimal, ContractError> {
+        checked_decimal(reserve, self.reserve)
     }
 }
 
+/// Multiplies by 10^places and rounds down to an integer amount
+fn to_uint(value: Decimal, places: u32) -> Result<Uint128, ContractError> {
+    let factor = 10u128
+        .checked_pow(places)
+        .ok_or(ContractError::AmountOverflow {})?;
+    let out = value
+        .checked_mul(checked_decimal(factor, 0)?)
+        .ok_or(ContractError::AmountOverflow {})?;
+    out.floor()
+        .to_u128()
+        .map(Uint128::new)
+        .ok_or(ContractError::AmountOverflow {})
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use cosmwasm_std::Decimal256;
+    use proptest::prelude::*;
+    use proptest::test_runner::TestCaseError;
+    use std::str::FromStr;
     // TODO: test DecimalPlaces return proper decimals
 
     #[test]
@@ -255,15 +394,15 @@ mod tests {
         // spot price is always 1.5 ATOM
         assert_eq!(
             StdDecimal::percent(150),
-            curve.spot_price(Uint128::new(123))
+            curve.spot_price(Uint128::new(123)).unwrap()
         );
 
         // if we have 30 STEP, we should have 45 ATOM
-        let reserve = curve.reserve(Uint128::new(30_000_000_000));
+        let reserve = curve.reserve(Uint128::new(30_000_000_000)).unwrap();
         assert_eq!(Uint128::new(45_000_000), reserve);
 
         // if we have 36 ATOM, we should have 24 STEP
-        let supply = curve.supply(Uint128::new(36_000_000));
+        let supply = curve.supply(Uint128::new(36_000_000)).unwrap();
         assert_eq!(Uint128::new(24_000_000_000), supply);
     }
 
@@ -278,33 +417,33 @@ mod tests {
         // spot price is 0.1 with 1 USDT supply
         assert_eq!(
             StdDecimal::permille(100),
-            curve.spot_price(Uint128::new(100))
+            curve.spot_price(Uint128::new(100)).unwrap()
         );
         // spot price is 1.7 with 17 USDT supply
         assert_eq!(
             StdDecimal::permille(1700),
-            curve.spot_price(Uint128::new(1700))
+            curve.spot_price(Uint128::new(1700)).unwrap()
         );
         // spot price is 0.212 with 2.12 USDT supply
         assert_eq!(
             StdDecimal::permille(212),
-            curve.spot_price(Uint128::new(212))
+            curve.spot_price(Uint128::new(212)).unwrap()
         );
 
         // if we have 10 USDT, we should have 5 BTC
-        let reserve = curve.reserve(Uint128::new(1000));
+        let reserve = curve.reserve(Uint128::new(1000)).unwrap();
         assert_eq!(Uint128::new(500_000_000), reserve);
         // if we have 20 USDT, we should have 20 BTC
-        let reserve = curve.reserve(Uint128::new(2000));
+        let reserve = curve.reserve(Uint128::new(2000)).unwrap();
         assert_eq!(Uint128::new(2_000_000_000), reserve);
 
         // if we have 1.25 BTC, we should have 5 USDT
-        let supply = curve.supply(Uint128::new(125_000_000));
+        let supply = curve.supply(Uint128::new(125_000_000)).unwrap();
         assert_eq!(Uint128::new(500), supply);
         // test square root rounding
         // TODO: test when supply has many more decimal places than reserve
         // if we have 1.11 BTC, we should have 4.7116875957... USDT
-        let supply = curve.supply(Uint128::new(111_000_000));
+        let supply = curve.supply(Uint128::new(111_000_000)).unwrap();
         assert_eq!(Uint128::new(471), supply);
     }
 
@@ -319,38 +458,342 @@ mod tests {
         // spot price is 0.35 with 1 TREE supply
         assert_eq!(
             StdDecimal::percent(35),
-            curve.spot_price(Uint128::new(1_000_000))
+            curve.spot_price(Uint128::new(1_000_000)).unwrap()
         );
         // spot price is 3.5 with 100 TREE supply
         assert_eq!(
             StdDecimal::percent(350),
-            curve.spot_price(Uint128::new(100_000_000))
+            curve.spot_price(Uint128::new(100_000_000)).unwrap()
         );
-        // spot price should be 23.478713763747788 with 4500 TREE supply (test rounding and reporting here)
-        // rounds off around 8-9 sig figs (note diff for last points)
+        // spot price should be 23.478713763747791812... with 4500 TREE supply (test rounding and
+        // reporting here), exact up to the 18 decimal places of Decimal, rounded down
         assert_eq!(
-            StdDecimal::from_ratio(2347871365u128, 100_000_000u128),
-            curve.spot_price(Uint128::new(4_500_000_000))
+            StdDecimal::from_str("23.478713763747791812").unwrap(),
+            curve.spot_price(Uint128::new(4_500_000_000)).unwrap()
         );
 
         // if we have 1 TREE, we should have 0.2333333333333 CHF
-        let reserve = curve.reserve(Uint128::new(1_000_000));
+        let reserve = curve.reserve(Uint128::new(1_000_000)).unwrap();
         assert_eq!(Uint128::new(23), reserve);
         // if we have 100 TREE, we should have 233.333333333 CHF
-        let reserve = curve.reserve(Uint128::new(100_000_000));
+        let reserve = curve.reserve(Uint128::new(100_000_000)).unwrap();
         assert_eq!(Uint128::new(23_333), reserve);
         // test rounding
         // if we have 235 TREE, we should have 840.5790828021146 CHF
-        let reserve = curve.reserve(Uint128::new(235_000_000));
+        let reserve = curve.reserve(Uint128::new(235_000_000)).unwrap();
         assert_eq!(Uint128::new(84_057), reserve); // round down
 
         // // if we have 0.23 CHF, we should have 0.990453 TREE (round down)
-        let supply = curve.supply(Uint128::new(23));
-        assert_eq!(Uint128::new(990_000), supply);
+        let supply = curve.supply(Uint128::new(23)).unwrap();
+        assert_eq!(Uint128::new(990_453), supply);
         // if we have 840.58 CHF, we should have 235.000170 TREE (round down)
-        let supply = curve.supply(Uint128::new(84058));
-        assert_eq!(Uint128::new(235_000_000), supply);
+        let supply = curve.supply(Uint128::new(84058)).unwrap();
+        assert_eq!(Uint128::new(235_000_170), supply);
+    }
+
+    #[test]
+    fn scale_decimal_rounds_down() {
+        // 2/3 keeps all 28 decimal places and never rounds up
+        let (num, scale) =
+            scale_decimal(Uint128::new(1), 0, Uint128::new(2), Uint128::new(3)).unwrap();
+        assert_eq!(
+            (num, scale),
+            (Uint128::new(6_666_666_666_666_666_666_666_666_666), 28)
+        );
+        // trailing zeros are dropped
+        let (num, scale) =
+            scale_decimal(Uint128::new(15), 1, Uint128::new(4), Uint128::new(3)).unwrap();
+        assert_eq!((num, scale), (Uint128::new(2), 0));
+        // nothing left after rounding, or too big for a Decimal
+        assert_eq!(
+            scale_decimal(Uint128::new(1), 28, Uint128::new(1), Uint128::new(10)),
+            None
+        );
+        assert_eq!(
+            scale_decimal(Uint128::new(1), 0, Uint128::MAX, Uint128::new(1)),
+            None
+        );
     }
 
-    // Idea: generic test that curve.supply(curve.reserve(supply)) == supply (or within some small rounding margin)
+    #[test]
+    fn overflow_returns_error() {
+        // amounts and parameters that do not fit in a Decimal
+        assert_eq!(
+            checked_decimal(Uint128::MAX, 0).unwrap_err(),
+            ContractError::DecimalOverflow {
+                amount: Uint128::MAX,
+                decimals: 0
+            }
+        );
+        assert_eq!(
+            checked_decimal(1u128, 29).unwrap_err(),
+            ContractError::DecimalOverflow {
+                amount: Uint128::new(1),
+                decimals: 29
+            }
+        );
+
+        // results that do not fit in a Uint128
+        let curve = Constant::new(decimal(15u128, 1), DecimalPlaces::new(18, 18));
+        assert_eq!(
+            curve.reserve(Uint128::MAX).unwrap_err(),
+            ContractError::AmountOverflow {}
+        );
+        let curve = Linear::new(decimal(1u128, 1), DecimalPlaces::new(0, 0));
+        let supply = Uint128::new(10u128.pow(28));
+        assert_eq!(
+            curve.reserve(supply).unwrap_err(),
+            ContractError::AmountOverflow {}
+        );
+        assert_eq!(
+            curve.spot_price(Uint128::MAX).unwrap_err(),
+            ContractError::PriceOverflow {}
+        );
+
+        // intermediate results that do not even fit in a Uint512
+        let curve = SquareRoot::new(decimal(1u128, 28), DecimalPlaces::new(18, 0));
+        assert_eq!(
+            curve.supply(Uint128::MAX).unwrap_err(),
+            ContractError::CurveOverflow {}
+        );
+
+        // DecimalPlaces does not panic on out of range places either
+        assert_eq!(
+            DecimalPlaces::new(0, 40)
+                .to_reserve(Decimal::ONE)
+                .unwrap_err(),
+            ContractError::AmountOverflow {}
+        );
+    }
+
+    /// Up to a billion whole tokens plus some dust, in units with the given decimal places.
+    /// Together with `params` and `decimal_places` this keeps every result within a Uint128
+    fn amount(tokens: u128, dust: u128, places: u32) -> Uint128 {
+        let unit = 10u128.pow(places);
+        Uint128::new(tokens * unit + dust % unit)
+    }
+
+    fn tokens() -> impl Strategy<Value = (u128, u128)> {
+        (0u128..1_000_000_000, any::<u128>())
+    }
+
+    /// Curve parameters from 10^-6 to 10^6
+    fn params() -> impl Strategy<Value = Decimal> {
+        (1u128..1_000_000, 0u32..=6).prop_map(|(param, scale)| decimal(param, scale))
+    }
+
+    fn decimal_places() -> impl Strategy<Value = DecimalPlaces> {
+        (0u8..=12, 0u8..=12).prop_map(|(supply, reserve)| DecimalPlaces::new(supply, reserve))
+    }
+
+    /// Returns `num * 10^exp / den` as a fraction of integers, without rounding
+    fn exact_ratio(num: Uint512, den: Uint512, exp: i64) -> (Uint512, Uint512) {
+        let factor = Uint512::from(10u8).pow(exp.unsigned_abs() as u32);
+        if exp >= 0 {
+            (num * factor, den)
+        } else {
+            (num, den * factor)
+        }
+    }
+
+    /// Fails the test case if the curve math returned an error
+    fn ok<T: std::fmt::Debug>(result: Result<T, ContractError>) -> Result<T, TestCaseError> {
+        prop_assert!(result.is_ok(), "unexpected error: {:?}", result);
+        Ok(result.unwrap())
+    }
+
+    /// Selling what you bought must never release more than was paid in, and
+    /// buying with what a supply is worth must never issue more than that supply
+    fn check_rounding(
+        curve: &dyn Curve,
+        supply: Uint128,
+        reserve: Uint128,
+    ) -> Result<(), TestCaseError> {
+        let bought = ok(curve.supply(ok(curve.reserve(supply))?))?;
+        prop_assert!(bought <= supply, "supply {} > {}", bought, supply);
+        let paid = ok(curve.reserve(ok(curve.supply(reserve))?))?;
+        prop_assert!(paid <= reserve, "reserve {} > {}", paid, reserve);
+        Ok(())
+    }
+
+    /// Buying or holding more must never be worth less
+    fn check_monotonic(
+        curve: &dyn Curve,
+        supply: (Uint128, Uint128),
+        reserve: (Uint128, Uint128),
+    ) -> Result<(), TestCaseError> {
+        prop_assert!(ok(curve.reserve(supply.0))? <= ok(curve.reserve(supply.1))?);
+        prop_assert!(ok(curve.supply(reserve.0))? <= ok(curve.supply(reserve.1))?);
+        prop_assert!(ok(curve.spot_price(supply.0))? <= ok(curve.spot_price(supply.1))?);
+        Ok(())
+    }
+
+    proptest! {
+        #[test]
+        fn constant_rounding_safe(
+            (supply, supply_dust) in tokens(),
+            (reserve, reserve_dust) in tokens(),
+            value in params(),
+            places in decimal_places(),
+        ) {
+            let supply = amount(supply, supply_dust, places.supply);
+            let reserve = amount(reserve, reserve_dust, places.reserve);
+            check_rounding(&Constant::new(value, places), supply, reserve)?;
+        }
+
+        #[test]
+        fn linear_rounding_safe(
+            (supply, supply_dust) in tokens(),
+            (reserve, reserve_dust) in tokens(),
+            slope in params(),
+            places in decimal_places(),
+        ) {
+            let supply = amount(supply, supply_dust, places.supply);
+            let reserve = amount(reserve, reserve_dust, places.reserve);
+            check_rounding(&Linear::new(slope, places), supply, reserve)?;
+        }
+
+        #[test]
+        fn out_of_range_amounts_overflow(
+            amount in Uint128::MAX.u128() / 2 + 1..=Uint128::MAX.u128(),
+            param in 2u128..1_000_000,
+            places in 0u8..=12,
+        ) {
+            // anything worth more than fits in a Uint128 is an error, not a panic
+            let amount = Uint128::new(amount);
+            let places = DecimalPlaces::new(places, places);
+            let param = decimal(param, 0);
+            let curves: [Box<dyn Curve>; 3] = [
+                Box::new(Constant::new(param, places.clone())),
+                Box::new(Linear::new(param, places.clone())),
+                Box::new(SquareRoot::new(param, places.clone())),
+            ];
+            for curve in curves.iter() {
+                prop_assert_eq!(curve.reserve(amount), Err(ContractError::AmountOverflow {}));
+            }
+            let curve = Constant::new(decimal(1u128, 1), places);
+            prop_assert_eq!(curve.supply(amount), Err(ContractError::AmountOverflow {}));
+        }
+
+        #[test]
+        fn isqrt_is_exact(n in any::<[u8; 50]>()) {
+            // up to 400 bits, so (r + 1)^2 still fits
+            let mut bytes = [0u8; 64];
+            bytes[14..].copy_from_slice(&n);
+            let n = Uint512::from_be_bytes(bytes);
+            let r = isqrt(n);
+            prop_assert!(r * r <= n);
+            let next = r + Uint512::one();
+            prop_assert!(next * next > n);
+        }
+
+        #[test]
+        fn icbrt_is_exact(n in any::<[u8; 50]>()) {
+            let mut bytes = [0u8; 64];
+            bytes[14..].copy_from_slice(&n);
+            let n = Uint512::from_be_bytes(bytes);
+            let r = icbrt(n);
+            prop_assert!(r * r * r <= n);
+            let next = r + Uint512::one();
+            prop_assert!(next * next * next > n);
+        }
+
+        #[test]
+        fn linear_supply_is_floor_of_root(
+            (reserve, dust) in tokens(),
+            slope in 1u128..1_000_000,
+            scale in 0u32..=6,
+            places in decimal_places(),
+        ) {
+            // supply is the largest s with s^2 <= 2 * reserve * 10^exp / slope
+            let reserve = amount(reserve, dust, places.reserve);
+            let curve = Linear::new(decimal(slope, scale), places.clone());
+            let s = Uint512::from(ok(curve.supply(reserve))?);
+            let exp = scale as i64 + 2 * places.supply as i64 - places.reserve as i64;
+            let reserve = Uint512::from(reserve);
+            let (num, den) = exact_ratio(reserve + reserve, Uint512::from(slope), exp);
+            prop_assert!(s * s * den <= num);
+            let next = s + Uint512::one();
+            prop_assert!(next * next * den > num);
+        }
+
+        #[test]
+        fn sqrt_supply_is_floor_of_root(
+            (reserve, dust) in tokens(),
+            slope in 1u128..1_000_000,
+            scale in 0u32..=6,
+            places in decimal_places(),
+        ) {
+            // supply is the largest s with s^3 <= 9 * reserve^2 * 10^exp / (4 * slope^2)
+            let reserve = amount(reserve, dust, places.reserve);
+            let curve = SquareRoot::new(decimal(slope, scale), places.clone());
+            let s = Uint512::from(ok(curve.supply(reserve))?);
+            let exp = 2 * scale as i64 + 3 * places.supply as i64 - 2 * places.reserve as i64;
+            let reserve = Uint512::from(reserve);
+            let slope = Uint512::from(slope);
+            let (num, den) = exact_ratio(
+                reserve * reserve * Uint512::from(9u8),
+                slope * slope * Uint512::from(4u8),
+                exp,
+            );
+            prop_assert!(s * s * s * den <= num);
+            let next = s + Uint512::one();
+            prop_assert!(next * next * next * den > num);
+        }
+
+        #[test]
+        fn sqrt_spot_price_matches_reference(
+            (supply, dust) in tokens(),
+            slope in 1u128..1_000_000,
+            scale in 0u32..=6,
+            places in decimal_places(),
+        ) {
+            let supply = amount(supply, dust, places.supply).u128();
+            let curve = SquareRoot::new(decimal(slope, scale), places.clone());
+            let price = ok(curve.spot_price(Uint128::new(supply)))?;
+
+            // Decimal256 keeps 18 decimal places through the root
+            let x = Decimal256::from_atomics(supply, places.supply).unwrap();
+            let slope = Decimal256::from_atomics(slope, scale).unwrap();
+            let reference = x.sqrt() * slope;
+            let price = Decimal256::from_atomics(price.atomics(), 18).unwrap();
+            let tolerance = reference * Decimal256::from_ratio(1u8, 1_000_000_000_000u128)
+                + Decimal256::from_atomics(slope.atomics(), 36).unwrap()
+                + Decimal256::from_atomics(1u8, 18).unwrap();
+            prop_assert!(
+                price.abs_diff(reference) <= tolerance,
+                "price {} reference {}", price, reference
+            );
+        }
+
+        #[test]
+        fn curves_monotonic(
+            a in tokens(),
+            b in tokens(),
+            c in tokens(),
+            d in tokens(),
+            param in params(),
+            places in decimal_places(),
+        ) {
+            let (a, b) = (amount(a.0, a.1, places.supply), amount(b.0, b.1, places.supply));
+            let (c, d) = (amount(c.0, c.1, places.reserve), amount(d.0, d.1, places.reserve));
+            let supply = (a.min(b), a.max(b));
+            let reserve = (c.min(d), c.max(d));
+            check_monotonic(&Constant::new(param, places.clone()), supply, reserve)?;
+            check_monotonic(&Linear::new(param, places.clone()), supply, reserve)?;
+            check_monotonic(&SquareRoot::new(param, places), supply, reserve)?;
+        }
+
+        #[test]
+        fn sqrt_rounding_safe(
+            (supply, supply_dust) in tokens(),
+            (reserve, reserve_dust) in tokens(),
+            slope in params(),
+            places in decimal_places(),
+        ) {
+            let supply = amount(supply, supply_dust, places.supply);
+            let reserve = amount(reserve, reserve_dust, places.reserve);
+            check_rounding(&SquareRoot::new(slope, places), supply, reserve)?;
+        }
+    }
 }
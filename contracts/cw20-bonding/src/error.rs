@@ -1,4 +1,5 @@
-use cosmwasm_std::StdError;
+use cosmwasm_std::{StdError, Uint128};
+use cw_controllers::AdminError;
 use cw_utils::PaymentError;
 use thiserror::Error;
 
@@ -13,6 +14,78 @@ pub enum ContractError {
     #[error("{0}")]
     Payment(#[from] PaymentError),
 
+    #[error("{0}")]
+    Admin(#[from] AdminError),
+
     #[error("Unauthorized")]
     Unauthorized {},
+
+    #[error("This curve is backed by a cw20 token, buy by sending it to the contract")]
+    Cw20Reserve {},
+
+    #[error("This curve is backed by a native token, buy with Buy {{}}")]
+    NativeReserve {},
+
+    #[error("Curve parameter must be greater than zero")]
+    InvalidCurve {},
+
+    #[error("Curve calculation overflowed")]
+    CurveOverflow {},
+
+    #[error("{amount} with {decimals} decimal places is too large for curve math")]
+    DecimalOverflow { amount: Uint128, decimals: u32 },
+
+    #[error("Curve result does not fit in an amount")]
+    AmountOverflow {},
+
+    #[error("Spot price does not fit in a Decimal")]
+    PriceOverflow {},
+
+    #[error("New curve cannot be scaled to match the current reserve")]
+    CurveMismatch {},
+
+    #[error("Cannot migrate from {previous_contract}")]
+    CannotMigrate { previous_contract: String },
+
+    #[error("Cannot migrate from newer version {previous_version}")]
+    CannotMigrateVersion { previous_version: String },
+
+    #[error("Semver parsing error: {0}")]
+    SemVer(String),
+
+    #[error("Invalid hatch: {reason}")]
+    InvalidHatch { reason: String },
+
+    #[error("Not allowed while the hatch is active")]
+    HatchActive {},
+
+    #[error("The hatch is already closed")]
+    HatchClosed {},
+
+    #[error("Only allowlisted hatchers may buy during the hatch")]
+    NotHatcher {},
+
+    #[error("Hatch funding cap exceeded, only {remaining} more can be raised")]
+    HatchCapExceeded { remaining: Uint128 },
+
+    #[error("Nothing to claim")]
+    NothingToClaim {},
+
+    #[error("Fee must be less than {max} basis points")]
+    InvalidFee { max: u16 },
+
+    #[error("A fee recipient is required when fees are set")]
+    MissingFeeRecipient {},
+
+    #[error("Supply and buy limits must be greater than zero")]
+    InvalidLimit {},
+
+    #[error("Cannot buy any more tokens, the supply cap or buy limit is reached")]
+    BuyLimitReached {},
+}
+
+impl From<semver::Error> for ContractError {
+    fn from(err: semver::Error) -> Self {
+        Self::SemVer(err.to_string())
+    }
 }
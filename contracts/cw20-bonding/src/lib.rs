@@ -1,6 +1,7 @@
 pub mod contract;
 pub mod curves;
 mod error;
+mod migrations;
 pub mod msg;
 pub mod state;
 
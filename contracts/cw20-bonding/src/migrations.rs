@@ -0,0 +1,31 @@
+// Migration logic for contracts with version: 0.14.2
+pub mod v0_14_2 {
+    use cosmwasm_schema::cw_serde;
+    use cosmwasm_std::{DepsMut, Uint128};
+    use cw20::Denom;
+    use cw_storage_plus::Item;
+
+    use crate::curves::DecimalPlaces;
+    use crate::state::{CurveState, CURVE_STATE};
+    use crate::ContractError;
+
+    /// CurveState as stored before the reserve could be a cw20 token
+    #[cw_serde]
+    struct LegacyCurveState {
+        reserve: Uint128,
+        supply: Uint128,
+        reserve_denom: String,
+        decimals: DecimalPlaces,
+    }
+
+    const LEGACY_CURVE_STATE: Item<LegacyCurveState> = Item::new("curve_state");
+
+    pub fn migrate_curve_state(deps: DepsMut) -> Result<(), ContractError> {
+        let legacy = LEGACY_CURVE_STATE.load(deps.storage)?;
+        let mut state = CurveState::new(Denom::Native(legacy.reserve_denom), legacy.decimals);
+        state.reserve = legacy.reserve;
+        state.supply = legacy.supply;
+        CURVE_STATE.save(deps.storage, &state)?;
+        Ok(())
+    }
+}
@@ -1,11 +1,20 @@
 use cosmwasm_schema::{cw_serde, QueryResponses};
 
-use crate::curves::{decimal, Constant, Curve, DecimalPlaces, Linear, SquareRoot};
-use cosmwasm_std::{Binary, Decimal, Uint128};
+use crate::curves::{
+    checked_decimal, decimal, scale_decimal, Constant, Curve, DecimalPlaces, Linear, SquareRoot,
+};
+use crate::state::Phase;
+use crate::ContractError;
+use cosmwasm_std::{Binary, Decimal, Timestamp, Uint128};
 use cw20::AllowanceResponse as Cw20AllowanceResponse;
 use cw20::BalanceResponse as Cw20BalanceResponse;
-use cw20::Expiration;
 use cw20::TokenInfoResponse as Cw20TokenInfoResponse;
+use cw20::{
+    AllAccountsResponse, AllAllowancesResponse, AllSpenderAllowancesResponse, Cw20ReceiveMsg,
+    Denom, DownloadLogoResponse, Expiration, Logo, MarketingInfoResponse,
+};
+use cw20_base::msg::InstantiateMarketingInfo;
+use cw_controllers::AdminResponse;
 
 #[cw_serde]
 pub struct InstantiateMsg {
@@ -17,12 +26,39 @@ pub struct InstantiateMsg {
     /// If it is eg. BTC, where a balance of 10^8 means 1 BTC, then use 8 here.
     pub decimals: u8,
 
-    /// this is the reserve token denom (only support native for now)
-    pub reserve_denom: String,
+    /// this is the reserve token, either a native denom or a cw20 contract
+    pub reserve_asset: ReserveAsset,
     /// number of decimal places for the reserve token, needed for proper curve math.
     /// Same format as decimals above, eg. if it is uatom, where 1 unit is 10^-6 ATOM, use 6 here
     pub reserve_decimals: u8,
 
+    /// fee taken from the reserve paid in on every buy, in basis points (100 = 1%). Zero if unset
+    #[serde(default)]
+    pub buy_fee_bps: u16,
+    /// fee taken from the reserve released on every sell, in basis points (100 = 1%). Zero if unset
+    #[serde(default)]
+    pub sell_fee_bps: u16,
+    /// address that receives the buy and sell fees. Required if any fee is non-zero
+    #[serde(default)]
+    pub fee_recipient: Option<String>,
+
+    /// the supply can never grow beyond this many tokens. A buy that would cross it only
+    /// mints up to the cap and refunds the rest of the reserve
+    pub max_supply: Option<Uint128>,
+    /// at most this many supply tokens can be bought in one transaction, the rest is refunded
+    pub max_buy: Option<Uint128>,
+    /// at most this many supply tokens can be bought by one address in total, the rest is refunded
+    pub max_buy_per_address: Option<Uint128>,
+
+    /// if set, the contract starts in a hatch phase before trading on `curve_type`
+    pub hatch: Option<HatchConfig>,
+
+    /// admin can switch the curve with `SwitchCurve`. If unset, the curve is fixed forever
+    pub admin: Option<String>,
+
+    /// optional project, description, marketing account and logo of the supply token
+    pub marketing: Option<InstantiateMarketingInfo>,
+
     /// enum to store the curve parameters used for this contract
     /// if you want to add a custom Curve, you should make a new contract that imports this one.
     /// write a custom `instantiate`, and then dispatch `your::execute` -> `cw20_bonding::do_execute`
@@ -30,6 +66,30 @@ pub struct InstantiateMsg {
     pub curve_type: CurveType,
 }
 
+#[cw_serde]
+pub struct HatchConfig {
+    /// only these addresses may buy during the hatch
+    pub allowlist: Vec<String>,
+    /// fixed price of one supply token during the hatch is `price * 10^-scale` reserve tokens
+    pub price: Uint128,
+    pub scale: u32,
+    /// the hatch closes once this many reserve tokens have been raised
+    pub funding_cap: Uint128,
+    /// receives `funding_pool_bps` basis points of every hatch contribution
+    pub funding_pool: String,
+    pub funding_pool_bps: u16,
+    /// hatch tokens vest linearly over this many seconds after the hatch closes
+    pub vesting_seconds: u64,
+}
+
+#[cw_serde]
+pub enum ReserveAsset {
+    /// A native token, sent along with `ExecuteMsg::Buy {}`
+    Native { denom: String },
+    /// A cw20 token, sent to this contract via `Send` with a `ReceiveMsg::Buy {}` hook
+    Cw20 { address: String },
+}
+
 pub type CurveFn = Box<dyn Fn(DecimalPlaces) -> Box<dyn Curve>>;
 
 #[cw_serde]
@@ -43,6 +103,20 @@ pub enum CurveType {
 }
 
 impl CurveType {
+    /// Ensures the curve parameter is non-zero and can be used in the curve math
+    pub fn validate(&self) -> Result<(), ContractError> {
+        let (param, scale) = match *self {
+            CurveType::Constant { value, scale } => (value, scale),
+            CurveType::Linear { slope, scale } => (slope, scale),
+            CurveType::SquareRoot { slope, scale } => (slope, scale),
+        };
+        if param.is_zero() {
+            return Err(ContractError::InvalidCurve {});
+        }
+        checked_decimal(param, scale)?;
+        Ok(())
+    }
+
     pub fn to_curve_fn(&self) -> CurveFn {
         match self.clone() {
             CurveType::Constant { value, scale } => {
@@ -65,6 +139,27 @@ impl CurveType {
             }
         }
     }
+
+    /// Returns the same kind of curve with its parameter multiplied by `numerator / denominator`.
+    /// Every curve is linear in its parameter, so `reserve(supply)` is scaled by the same ratio.
+    /// Returns None if the new parameter cannot be represented.
+    pub fn rescale(&self, numerator: Uint128, denominator: Uint128) -> Option<CurveType> {
+        let curve_type = match *self {
+            CurveType::Constant { value, scale } => {
+                let (value, scale) = scale_decimal(value, scale, numerator, denominator)?;
+                CurveType::Constant { value, scale }
+            }
+            CurveType::Linear { slope, scale } => {
+                let (slope, scale) = scale_decimal(slope, scale, numerator, denominator)?;
+                CurveType::Linear { slope, scale }
+            }
+            CurveType::SquareRoot { slope, scale } => {
+                let (slope, scale) = scale_decimal(slope, scale, numerator, denominator)?;
+                CurveType::SquareRoot { slope, scale }
+            }
+        };
+        Some(curve_type)
+    }
 }
 
 #[cw_serde]
@@ -72,6 +167,22 @@ pub enum ExecuteMsg {
     /// Buy will attempt to purchase as many supply tokens as possible.
     /// You must send only reserve tokens in that message
     Buy {},
+    /// Like `Buy`, but the tokens are minted to `recipient`. Any refund still goes to the sender
+    BuyFor { recipient: String },
+    /// Like `Buy`, but the tokens are minted to `contract` and delivered with a
+    /// `Cw20ReceiveMsg` carrying `msg`, just like `Send`. Not possible during the hatch
+    BuyAndSend { contract: String, msg: Binary },
+    /// Receive is the entry point for a cw20 reserve token. The embedded msg must be a `ReceiveMsg`
+    Receive(Cw20ReceiveMsg),
+    /// Admin only. Replaces the bonding curve. The parameter of `curve_type` is rescaled so the
+    /// new curve values the current supply at exactly the current reserve, only the shape is kept.
+    SwitchCurve { curve_type: CurveType },
+    /// Admin only. Sets a new admin, or removes the admin if None
+    UpdateAdmin { admin: Option<String> },
+    /// Admin only. Closes the hatch before the funding cap is reached and starts trading
+    CloseHatch {},
+    /// Releases the hatch tokens of the sender that have vested so far
+    ClaimVested {},
 
     /// Implements CW20. Transfer is a base message to move tokens to another account without triggering actions
     Transfer { recipient: String, amount: Uint128 },
@@ -117,6 +228,25 @@ pub enum ExecuteMsg {
     },
     /// Implements CW20 "approval" extension. Destroys tokens forever
     BurnFrom { owner: String, amount: Uint128 },
+    /// Implements CW20 "marketing" extension. Only the marketing account may update the
+    /// project, description and marketing account. Empty strings clear a field.
+    UpdateMarketing {
+        project: Option<String>,
+        description: Option<String>,
+        marketing: Option<String>,
+    },
+    /// Implements CW20 "marketing" extension. Only the marketing account may upload a new logo
+    UploadLogo(Logo),
+}
+
+#[cw_serde]
+pub enum ReceiveMsg {
+    /// Buy will purchase as many supply tokens as possible with the sent cw20 reserve tokens
+    Buy {},
+    /// Same as `ExecuteMsg::BuyFor`, paid with the sent cw20 reserve tokens
+    BuyFor { recipient: String },
+    /// Same as `ExecuteMsg::BuyAndSend`, paid with the sent cw20 reserve tokens
+    BuyAndSend { contract: String, msg: Binary },
 }
 
 #[cw_serde]
@@ -125,6 +255,18 @@ pub enum QueryMsg {
     /// Returns the reserve and supply quantities, as well as the spot price to buy 1 token
     #[returns(CurveInfoResponse)]
     CurveInfo {},
+    /// Returns the curve type currently in use
+    #[returns(CurveType)]
+    CurveType {},
+    /// Returns the admin who may switch the curve, if any
+    #[returns(AdminResponse)]
+    Admin {},
+    /// Returns the hatch configuration and progress. Fails if there never was a hatch
+    #[returns(HatchResponse)]
+    Hatch {},
+    /// Returns the contribution and vesting of one hatcher. Fails if not on the allowlist
+    #[returns(HatcherResponse)]
+    Hatcher { address: String },
     /// Implements CW20. Returns the current balance of the given address, 0 if unset.
     #[returns(Cw20BalanceResponse)]
     Balance { address: String },
@@ -135,6 +277,37 @@ pub enum QueryMsg {
     /// Returns how much spender can use from owner account, 0 if unset.
     #[returns(Cw20AllowanceResponse)]
     Allowance { owner: String, spender: String },
+    /// Implements CW20 "enumerable" extension.
+    /// Returns all allowances this owner has approved. Supports pagination.
+    #[returns(AllAllowancesResponse)]
+    AllAllowances {
+        owner: String,
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+    /// Implements CW20 "enumerable" extension.
+    /// Returns all allowances this spender has been granted. Supports pagination.
+    #[returns(AllSpenderAllowancesResponse)]
+    AllSpenderAllowances {
+        spender: String,
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+    /// Implements CW20 "enumerable" extension.
+    /// Returns all accounts that have balances. Supports pagination.
+    #[returns(AllAccountsResponse)]
+    AllAccounts {
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+    /// Implements CW20 "marketing" extension.
+    /// Returns the project, description, marketing account and logo info of the token
+    #[returns(MarketingInfoResponse)]
+    MarketingInfo {},
+    /// Implements CW20 "marketing" extension.
+    /// Downloads the embedded logo data. Fails if no logo is stored in the contract.
+    #[returns(DownloadLogoResponse)]
+    DownloadLogo {},
 }
 
 #[cw_serde]
@@ -144,5 +317,47 @@ pub struct CurveInfoResponse {
     // how many supply tokens have been issued
     pub supply: Uint128,
     pub spot_price: Decimal,
-    pub reserve_denom: String,
+    pub reserve_denom: Denom,
+    // fee taken on buys, in basis points
+    pub buy_fee_bps: u16,
+    // fee taken on sells, in basis points
+    pub sell_fee_bps: u16,
+    pub fee_recipient: Option<String>,
+    // how many reserve tokens have been paid out as fees
+    pub fees_collected: Uint128,
+    pub phase: Phase,
+    // the supply can never grow beyond this
+    pub max_supply: Option<Uint128>,
+    // most tokens that can be bought in one transaction
+    pub max_buy: Option<Uint128>,
+    // most tokens that can be bought by one address in total
+    pub max_buy_per_address: Option<Uint128>,
+}
+
+#[cw_serde]
+pub struct HatchResponse {
+    pub price: Uint128,
+    pub scale: u32,
+    pub funding_cap: Uint128,
+    pub funding_pool: String,
+    pub funding_pool_bps: u16,
+    pub vesting_seconds: u64,
+    // how many reserve tokens have been contributed, including the funding pool share
+    pub raised: Uint128,
+    pub closed_at: Option<Timestamp>,
+}
+
+#[cw_serde]
+pub struct HatcherResponse {
+    pub contributed: Uint128,
+    pub tokens: Uint128,
+    pub claimed: Uint128,
+    // how many tokens could be claimed right now
+    pub claimable: Uint128,
+}
+
+#[cw_serde]
+pub struct MigrateMsg {
+    /// optionally set an admin, eg. for contracts instantiated before there was one
+    pub admin: Option<String>,
 }
@@ -1,7 +1,9 @@
 use cosmwasm_schema::cw_serde;
 
-use cosmwasm_std::Uint128;
-use cw_storage_plus::Item;
+use cosmwasm_std::{Addr, Timestamp, Uint128};
+use cw20::Denom;
+use cw_controllers::Admin;
+use cw_storage_plus::{Item, Map};
 
 use crate::curves::DecimalPlaces;
 use crate::msg::CurveType;
@@ -14,20 +16,61 @@ pub struct CurveState {
     /// supply is how many tokens this contract has issued
     pub supply: Uint128,
 
-    // the denom of the reserve token
-    pub reserve_denom: String,
+    // the denom of the reserve token, either native or a cw20 contract
+    pub reserve_denom: Denom,
 
     // how to normalize reserve and supply
     pub decimals: DecimalPlaces,
+
+    // fee taken from the reserve paid in on buy, in basis points
+    #[serde(default)]
+    pub buy_fee_bps: u16,
+    // fee taken from the reserve released on sell, in basis points
+    #[serde(default)]
+    pub sell_fee_bps: u16,
+    // who receives the buy and sell fees
+    #[serde(default)]
+    pub fee_recipient: Option<Addr>,
+    // total reserve tokens paid out as fees so far
+    #[serde(default)]
+    pub fees_collected: Uint128,
+
+    // whether we are still in the hatch or trading on the curve
+    #[serde(default)]
+    pub phase: Phase,
+
+    // most supply tokens that can be bought in one transaction
+    #[serde(default)]
+    pub max_buy: Option<Uint128>,
+    // most supply tokens that can be bought by one address in total, tracked in BOUGHT
+    #[serde(default)]
+    pub max_buy_per_address: Option<Uint128>,
+}
+
+#[cw_serde]
+#[derive(Default)]
+pub enum Phase {
+    /// Only allowlisted hatchers may buy, at a fixed price. Selling is disabled
+    Hatch,
+    /// Anyone can buy and sell on the curve
+    #[default]
+    Open,
 }
 
 impl CurveState {
-    pub fn new(reserve_denom: String, decimals: DecimalPlaces) -> Self {
+    pub fn new(reserve_denom: Denom, decimals: DecimalPlaces) -> Self {
         CurveState {
             reserve: Uint128::zero(),
             supply: Uint128::zero(),
             reserve_denom,
             decimals,
+            buy_fee_bps: 0,
+            sell_fee_bps: 0,
+            fee_recipient: None,
+            fees_collected: Uint128::zero(),
+            phase: Phase::Open,
+            max_buy: None,
+            max_buy_per_address: None,
         }
     }
 }
@@ -35,3 +78,43 @@ impl CurveState {
 pub const CURVE_STATE: Item<CurveState> = Item::new("curve_state");
 
 pub const CURVE_TYPE: Item<CurveType> = Item::new("curve_type");
+
+/// How many supply tokens each address bought on the curve, only tracked with `max_buy_per_address`
+pub const BOUGHT: Map<&Addr, Uint128> = Map::new("bought");
+
+pub const ADMIN: Admin = Admin::new("admin");
+
+/// Hatch is the initial funding phase of an augmented bonding curve
+#[cw_serde]
+pub struct Hatch {
+    /// fixed price of one supply token during the hatch is `price * 10^-scale` reserve tokens
+    pub price: Uint128,
+    pub scale: u32,
+    /// the hatch closes once this many reserve tokens have been raised
+    pub funding_cap: Uint128,
+    /// receives `funding_pool_bps` of every hatch contribution, the rest goes into the reserve
+    pub funding_pool: Addr,
+    pub funding_pool_bps: u16,
+    /// hatch tokens vest linearly over this many seconds after the hatch closes
+    pub vesting_seconds: u64,
+    /// total reserve tokens contributed so far, including the funding pool share
+    pub raised: Uint128,
+    /// when the hatch closed, vesting starts from here
+    pub closed_at: Option<Timestamp>,
+}
+
+pub const HATCH: Item<Hatch> = Item::new("hatch");
+
+/// Contribution of an allowlisted hatcher. Hatch tokens are held by the contract until vested
+#[cw_serde]
+#[derive(Default)]
+pub struct Hatcher {
+    /// reserve tokens paid in during the hatch
+    pub contributed: Uint128,
+    /// supply tokens bought during the hatch
+    pub tokens: Uint128,
+    /// supply tokens already released to the hatcher
+    pub claimed: Uint128,
+}
+
+pub const HATCHERS: Map<&Addr, Hatcher> = Map::new("hatchers");
@@ -1,11 +1,15 @@
+use std::convert::TryFrom;
+
 #[cfg(not(feature = "library"))]
 use cosmwasm_std::entry_point;
 use cosmwasm_std::{
-    coin, to_binary, Addr, BankMsg, Binary, Decimal, Deps, DepsMut, DistributionMsg, Env,
-    MessageInfo, QuerierWrapper, Response, StakingMsg, StdError, StdResult, Uint128, WasmMsg,
+    coin, coins, from_binary, to_binary, Addr, BankMsg, Binary, Decimal, Deps, DepsMut,
+    DistributionMsg, Empty, Env, Event, MessageInfo, Order, QuerierWrapper, Response, StakingMsg,
+    StdError, StdResult, Storage, Timestamp, Uint128, Uint256, WasmMsg,
 };
 
-use cw2::set_contract_version;
+use cw2::{get_contract_version, set_contract_version};
+use cw20::Cw20ReceiveMsg;
 use cw20_base::allowances::{
     execute_burn_from, execute_decrease_allowance, execute_increase_allowance, execute_send_from,
     execute_transfer_from, query_allowance,
@@ -14,13 +18,30 @@ use cw20_base::contract::{
     execute_burn, execute_mint, execute_send, execute_transfer, query_balance, query_token_info,
 };
 use cw20_base::state::{MinterData, TokenInfo, TOKEN_INFO};
+use cw_storage_plus::Bound;
+use cw_utils::{Duration, Expiration};
+use semver::Version;
 
 use crate::error::ContractError;
-use crate::msg::{ExecuteMsg, InstantiateMsg, InvestmentResponse, QueryMsg};
-use crate::state::{InvestmentInfo, Supply, CLAIMS, INVESTMENT, TOTAL_SUPPLY};
+use crate::migrations::v0_14_2;
+use crate::msg::{
+    AddressClaims, AllClaimsResponse, ExchangeRateResponse, ExecuteMsg, InstantiateMsg,
+    InvestmentResponse, MigrateMsg, QueryMsg, RateHistoryResponse, RateRecord, ReceiveMsg,
+    TimeWeightedRateResponse, UnbondingEntry, UnbondingScheduleResponse, ValidatorWeight,
+};
+use crate::state::{
+    add_unbonding, remove_unbonding, InvestmentInfo, RateObservation, Supply, CLAIMANTS, CLAIMS,
+    INVESTMENT, LAST_REINVEST, PENDING_OWNER, RATE_HISTORY, TOTAL_SUPPLY, UNBONDING,
+};
 
 const FALLBACK_RATIO: Decimal = Decimal::one();
 
+// settings for pagination
+const MAX_LIMIT: u32 = 30;
+const DEFAULT_LIMIT: u32 = 10;
+// addresses whose claims are indexed per migrate call
+const DEFAULT_MIGRATE_LIMIT: u32 = 100;
+
 // version info for migration info
 const CONTRACT_NAME: &str = "crates.io:cw20-staking";
 const CONTRACT_VERSION: &str = env!("CARGO_PKG_VERSION");
@@ -34,12 +55,43 @@ pub fn instantiate(
 ) -> Result<Response, ContractError> {
     set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
 
-    // ensure the validator is registered
+    if msg.exit_tax > Decimal::one() {
+        return Err(ContractError::InvalidExitTax {});
+    }
+    if msg.keeper_reward > Decimal::one() {
+        return Err(ContractError::InvalidKeeperReward {});
+    }
+    if msg.buffer_ratio > Decimal::one() {
+        return Err(ContractError::InvalidBufferRatio {});
+    }
+    if msg.instant_unbond_fee > Decimal::one() {
+        return Err(ContractError::InvalidInstantUnbondFee {});
+    }
+
+    // ensure the validators are registered, with a proper weight
+    if msg.validators.is_empty() {
+        return Err(ContractError::NoValidators {});
+    }
     let vals = deps.querier.query_all_validators()?;
-    if !vals.iter().any(|v| v.address == msg.validator) {
-        return Err(ContractError::NotInValidatorSet {
-            validator: msg.validator,
-        });
+    for (i, validator) in msg.validators.iter().enumerate() {
+        if !vals.iter().any(|v| v.address == validator.address) {
+            return Err(ContractError::NotInValidatorSet {
+                validator: validator.address.clone(),
+            });
+        }
+        if validator.weight == 0 {
+            return Err(ContractError::ZeroWeight {
+                validator: validator.address.clone(),
+            });
+        }
+        if msg.validators[..i]
+            .iter()
+            .any(|v| v.address == validator.address)
+        {
+            return Err(ContractError::DuplicateValidator {
+                validator: validator.address.clone(),
+            });
+        }
     }
 
     // store token info using cw20-base format
@@ -50,7 +102,7 @@ pub fn instantiate(
         total_supply: Uint128::zero(),
         // set self as minter, so we can properly execute mint and burn
         mint: Some(MinterData {
-            minter: env.contract.address,
+            minter: env.contract.address.clone(),
             cap: None,
         }),
     };
@@ -62,14 +114,19 @@ pub fn instantiate(
         exit_tax: msg.exit_tax,
         unbonding_period: msg.unbonding_period,
         bond_denom: denom,
-        validator: msg.validator,
+        validators: msg.validators,
         min_withdrawal: msg.min_withdrawal,
+        keeper_reward: msg.keeper_reward,
+        reinvest_interval: msg.reinvest_interval.filter(|seconds| *seconds > 0),
+        buffer_ratio: msg.buffer_ratio,
+        instant_unbond_fee: msg.instant_unbond_fee,
     };
     INVESTMENT.save(deps.storage, &invest)?;
 
     // set supply to 0
     let supply = Supply::default();
     TOTAL_SUPPLY.save(deps.storage, &supply)?;
+    record_rate(deps.storage, &env, &supply)?;
 
     Ok(Response::default())
 }
@@ -84,8 +141,32 @@ pub fn execute(
     match msg {
         ExecuteMsg::Bond {} => bond(deps, env, info),
         ExecuteMsg::Unbond { amount } => unbond(deps, env, info, amount),
+        ExecuteMsg::InstantUnbond { amount } => instant_unbond(deps, env, info, amount),
+        ExecuteMsg::Receive(wrapper) => receive(deps, env, info, wrapper),
         ExecuteMsg::Claim {} => claim(deps, env, info),
         ExecuteMsg::Reinvest {} => reinvest(deps, env, info),
+        ExecuteMsg::Rebalance {} => rebalance(deps, env, info),
+        ExecuteMsg::UpdateConfig {
+            exit_tax,
+            min_withdrawal,
+            keeper_reward,
+            reinvest_interval,
+            buffer_ratio,
+            instant_unbond_fee,
+        } => {
+            let update = ConfigUpdate {
+                exit_tax,
+                min_withdrawal,
+                keeper_reward,
+                reinvest_interval,
+                buffer_ratio,
+                instant_unbond_fee,
+            };
+            update_config(deps, info, update)
+        }
+        ExecuteMsg::ProposeOwner { owner } => propose_owner(deps, info, owner),
+        ExecuteMsg::AcceptOwnership {} => accept_ownership(deps, info),
+        ExecuteMsg::ChangeValidator { from, to } => change_validator(deps, env, info, from, to),
         ExecuteMsg::_BondAllTokens {} => _bond_all_tokens(deps, env, info),
 
         // these all come from cw20-base to implement the cw20 standard
@@ -141,8 +222,7 @@ fn get_bonded(querier: &QuerierWrapper, contract: &Addr) -> Result<Uint128, Cont
         return Ok(Uint128::zero());
     }
     let denom = bonds[0].amount.denom.as_str();
-    bonds.iter().fold(Ok(Uint128::zero()), |racc, d| {
-        let acc = racc?;
+    bonds.iter().try_fold(Uint128::zero(), |acc, d| {
         if d.amount.denom.as_str() != denom {
             Err(ContractError::DifferentBondDenom {
                 denom1: denom.into(),
@@ -154,17 +234,125 @@ fn get_bonded(querier: &QuerierWrapper, contract: &Addr) -> Result<Uint128, Cont
     })
 }
 
-fn assert_bonds(supply: &Supply, bonded: Uint128) -> Result<(), ContractError> {
-    if supply.bonded != bonded {
-        Err(ContractError::BondedMismatch {
+// split_by_weight divides amount between the validators by weight, rounding down.
+// Whatever is lost to rounding goes to the first validator
+fn split_by_weight(amount: Uint128, validators: &[ValidatorWeight]) -> Vec<(String, Uint128)> {
+    let total: u64 = validators.iter().map(|v| v.weight).sum();
+    let mut split: Vec<_> = validators
+        .iter()
+        .map(|v| (v.address.clone(), amount.multiply_ratio(v.weight, total)))
+        .collect();
+    let assigned: Uint128 = split.iter().map(|(_, a)| *a).sum();
+    split[0].1 += amount - assigned;
+    split
+}
+
+// delegate_by_weight returns the messages to delegate amount split by weight
+fn delegate_by_weight(invest: &InvestmentInfo, amount: Uint128) -> Vec<StakingMsg> {
+    split_by_weight(amount, &invest.validators)
+        .into_iter()
+        .filter(|(_, amount)| !amount.is_zero())
+        .map(|(validator, amount)| StakingMsg::Delegate {
+            validator,
+            amount: coin(amount.u128(), &invest.bond_denom),
+        })
+        .collect()
+}
+
+// undelegate_proportionally returns the messages to undelegate amount from all validators
+// in proportion to what is currently delegated to each of them
+fn undelegate_proportionally(
+    querier: &QuerierWrapper,
+    contract: &Addr,
+    amount: Uint128,
+    bonded: Uint128,
+) -> StdResult<Vec<StakingMsg>> {
+    let delegations = querier.query_all_delegations(contract)?;
+    let mut split: Vec<_> = delegations
+        .iter()
+        .map(|d| d.amount.amount.multiply_ratio(amount, bonded))
+        .collect();
+    // hand out what was lost to rounding to validators that still have stake left
+    let mut left = amount - split.iter().sum::<Uint128>();
+    for (part, d) in split.iter_mut().zip(&delegations) {
+        let extra = left.min(d.amount.amount - *part);
+        *part += extra;
+        left -= extra;
+    }
+    Ok(delegations
+        .into_iter()
+        .zip(split)
+        .filter(|(_, part)| !part.is_zero())
+        .map(|(d, part)| StakingMsg::Undelegate {
+            validator: d.validator,
+            amount: coin(part.u128(), d.amount.denom),
+        })
+        .collect())
+}
+
+// sync_bonded updates the cached bonded amount to what is actually delegated.
+// Less than expected means a validator was slashed, which lowers the value of every token,
+// so we take the loss and return an event reporting it
+fn sync_bonded(supply: &mut Supply, bonded: Uint128) -> Result<Option<Event>, ContractError> {
+    if bonded > supply.bonded {
+        return Err(ContractError::BondedMismatch {
             stored: supply.bonded,
             queried: bonded,
-        })
+        });
+    }
+    if bonded == supply.bonded {
+        return Ok(None);
+    }
+    let event = Event::new("slashed")
+        .add_attribute("stored_bonded", supply.bonded)
+        .add_attribute("queried_bonded", bonded)
+        .add_attribute("slashed", supply.bonded - bonded);
+    supply.bonded = bonded;
+    Ok(Some(event))
+}
+
+/// how many native tokens one derivative token is worth, not counting pending rewards
+fn stored_rate(supply: &Supply) -> Decimal {
+    if supply.issued.is_zero() {
+        FALLBACK_RATIO
     } else {
-        Ok(())
+        Decimal::from_ratio(supply.bonded + supply.buffer, supply.issued)
     }
 }
 
+/// the cumulative rate at the given time, if anything was recorded by then
+fn cumulative_rate(storage: &dyn Storage, seconds: u64) -> StdResult<Option<Uint256>> {
+    let last = RATE_HISTORY
+        .range(
+            storage,
+            None,
+            Some(Bound::inclusive(seconds)),
+            Order::Descending,
+        )
+        .next()
+        .transpose()?;
+    Ok(last.map(|(time, obs)| {
+        obs.cumulative + Uint256::from(obs.rate.atomics()) * Uint256::from(seconds - time)
+    }))
+}
+
+/// record_rate adds the current exchange rate to the history
+fn record_rate(storage: &mut dyn Storage, env: &Env, supply: &Supply) -> StdResult<()> {
+    let now = env.block.time.seconds();
+    let observation = RateObservation {
+        rate: stored_rate(supply),
+        cumulative: cumulative_rate(storage, now)?.unwrap_or_default(),
+    };
+    RATE_HISTORY.save(storage, now, &observation)
+}
+
+// buffer_top_up is how much of the incoming tokens should stay undelegated
+// to bring the liquidity buffer up to its target share of all staked value
+fn buffer_top_up(invest: &InvestmentInfo, supply: &Supply, incoming: Uint128) -> Uint128 {
+    let target = (supply.bonded + supply.buffer + incoming) * invest.buffer_ratio;
+    target.saturating_sub(supply.buffer).min(incoming)
+}
+
 pub fn bond(deps: DepsMut, env: Env, info: MessageInfo) -> Result<Response, ContractError> {
     // ensure we have the proper denom
     let invest = INVESTMENT.load(deps.storage)?;
@@ -182,18 +370,23 @@ pub fn bond(deps: DepsMut, env: Env, info: MessageInfo) -> Result<Response, Cont
 
     // calculate to_mint and update total supply
     let mut supply = TOTAL_SUPPLY.load(deps.storage)?;
-    // TODO: this is just a safety assertion - do we keep it, or remove caching?
-    // in the end supply is just there to cache the (expected) results of get_bonded() so we don't
-    // have expensive queries everywhere
-    assert_bonds(&supply, bonded)?;
-    let to_mint = if supply.issued.is_zero() || bonded.is_zero() {
+    // supply caches the (expected) results of get_bonded() so we don't have expensive queries
+    // everywhere. Catch up on any slashing before pricing the new tokens
+    let slashed = sync_bonded(&mut supply, bonded)?;
+    let value = supply.bonded + supply.buffer;
+    let to_mint = if supply.issued.is_zero() || value.is_zero() {
         FALLBACK_RATIO * payment.amount
     } else {
-        payment.amount.multiply_ratio(supply.issued, bonded)
+        payment.amount.multiply_ratio(supply.issued, value)
     };
-    supply.bonded = bonded + payment.amount;
+    // keep a share undelegated in the buffer and bond the rest
+    let to_buffer = buffer_top_up(&invest, &supply, payment.amount);
+    let to_bond = payment.amount - to_buffer;
+    supply.bonded += to_bond;
+    supply.buffer += to_buffer;
     supply.issued += to_mint;
     TOTAL_SUPPLY.save(deps.storage, &supply)?;
+    record_rate(deps.storage, &env, &supply)?;
 
     // call into cw20-base to mint the token, call as self as no one else is allowed
     let sub_info = MessageInfo {
@@ -202,31 +395,127 @@ pub fn bond(deps: DepsMut, env: Env, info: MessageInfo) -> Result<Response, Cont
     };
     execute_mint(deps, env, sub_info, info.sender.to_string(), to_mint)?;
 
-    // bond them to the validator
+    // bond them to the validators
     let res = Response::new()
-        .add_message(StakingMsg::Delegate {
-            validator: invest.validator,
-            amount: payment.clone(),
-        })
+        .add_messages(delegate_by_weight(&invest, to_bond))
+        .add_events(slashed)
         .add_attribute("action", "bond")
         .add_attribute("from", info.sender)
-        .add_attribute("bonded", payment.amount)
+        .add_attribute("bonded", to_bond)
+        .add_attribute("buffered", to_buffer)
         .add_attribute("minted", to_mint);
     Ok(res)
 }
 
 pub fn unbond(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    amount: Uint128,
+) -> Result<Response, ContractError> {
+    let recipient = info.sender.clone();
+    unbond_to(deps, env, info, recipient, amount)
+}
+
+/// receive handles derivative tokens sent here with a cw20 `Send`.
+/// By now they are already on the balance of this contract
+pub fn receive(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    wrapper: Cw20ReceiveMsg,
+) -> Result<Response, ContractError> {
+    // only our own token calls in here
+    if info.sender != env.contract.address {
+        return Err(ContractError::UnsupportedToken {});
+    }
+    let sender = deps.api.addr_validate(&wrapper.sender)?;
+    match from_binary(&wrapper.msg)? {
+        ReceiveMsg::Unbond { recipient } => {
+            let recipient = match recipient {
+                Some(recipient) => deps.api.addr_validate(&recipient)?,
+                None => sender,
+            };
+            // so we burn them from our own balance
+            let info = MessageInfo {
+                sender: env.contract.address.clone(),
+                funds: vec![],
+            };
+            unbond_to(deps, env, info, recipient, wrapper.amount)
+        }
+    }
+}
+
+/// unbond_to burns amount from the sender and gives the claim on the unbonded tokens to recipient
+fn unbond_to(
     mut deps: DepsMut,
     env: Env,
     info: MessageInfo,
+    recipient: Addr,
     amount: Uint128,
 ) -> Result<Response, ContractError> {
     let invest = INVESTMENT.load(deps.storage)?;
+    let remainder = burn_with_tax(deps.branch(), &env, &info, &invest, amount)?;
+
+    // re-calculate bonded to ensure we have real values
+    // bonded is the total number of tokens we have delegated from this address
+    let bonded = get_bonded(&deps.querier, &env.contract.address)?;
+
+    // calculate how many native tokens this is worth and update supply
+    let mut supply = TOTAL_SUPPLY.load(deps.storage)?;
+    // catch up on any slashing before pricing the burnt tokens
+    let slashed = sync_bonded(&mut supply, bonded)?;
+    let unbond = remainder.multiply_ratio(bonded + supply.buffer, supply.issued);
+    // undelegate what we can, and only if that is not enough take the rest from the buffer
+    let from_bonded = unbond.min(bonded);
+    supply.bonded = bonded - from_bonded;
+    supply.buffer = supply
+        .buffer
+        .checked_sub(unbond - from_bonded)
+        .map_err(StdError::overflow)?;
+    supply.issued = supply
+        .issued
+        .checked_sub(remainder)
+        .map_err(StdError::overflow)?;
+    supply.claims += unbond;
+    TOTAL_SUPPLY.save(deps.storage, &supply)?;
+    record_rate(deps.storage, &env, &supply)?;
+
+    let release_at = invest.unbonding_period.after(&env.block);
+    CLAIMS.create_claim(deps.storage, &recipient, unbond, release_at)?;
+    CLAIMANTS.save(deps.storage, &recipient, &Empty {})?;
+    add_unbonding(deps.storage, &release_at, unbond)?;
+
+    // unbond them from all validators alike
+    let undelegate = if from_bonded.is_zero() {
+        vec![]
+    } else {
+        undelegate_proportionally(&deps.querier, &env.contract.address, from_bonded, bonded)?
+    };
+    let res = Response::new()
+        .add_messages(undelegate)
+        .add_events(slashed)
+        .add_attribute("action", "unbond")
+        .add_attribute("to", recipient)
+        .add_attribute("unbonded", unbond)
+        .add_attribute("burnt", amount);
+    Ok(res)
+}
+
+// burn_with_tax burns amount from the sender and mints the exit tax to the owner.
+// Returns how many tokens are actually redeemed
+fn burn_with_tax(
+    mut deps: DepsMut,
+    env: &Env,
+    info: &MessageInfo,
+    invest: &InvestmentInfo,
+    amount: Uint128,
+) -> Result<Uint128, ContractError> {
     // ensure it is big enough to care
     if amount < invest.min_withdrawal {
         return Err(ContractError::UnbondTooSmall {
             min_bonded: invest.min_withdrawal,
-            denom: invest.bond_denom,
+            denom: invest.bond_denom.clone(),
         });
     }
     // calculate tax and remainer to unbond
@@ -240,51 +529,55 @@ pub fn unbond(
             funds: vec![],
         };
         // call into cw20-base to mint tokens to owner, call as self as no one else is allowed
-        execute_mint(
-            deps.branch(),
-            env.clone(),
-            sub_info,
-            invest.owner.to_string(),
-            tax,
-        )?;
+        execute_mint(deps, env.clone(), sub_info, invest.owner.to_string(), tax)?;
     }
+    Ok(amount.checked_sub(tax).map_err(StdError::overflow)?)
+}
 
-    // re-calculate bonded to ensure we have real values
-    // bonded is the total number of tokens we have delegated from this address
-    let bonded = get_bonded(&deps.querier, &env.contract.address)?;
+/// instant_unbond pays out of the liquidity buffer right away, instead of creating a claim.
+/// The instant unbond fee stays in the buffer, raising the value of all remaining tokens
+pub fn instant_unbond(
+    mut deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    amount: Uint128,
+) -> Result<Response, ContractError> {
+    let invest = INVESTMENT.load(deps.storage)?;
+    let remainder = burn_with_tax(deps.branch(), &env, &info, &invest, amount)?;
 
-    // calculate how many native tokens this is worth and update supply
-    let remainder = amount.checked_sub(tax).map_err(StdError::overflow)?;
+    let bonded = get_bonded(&deps.querier, &env.contract.address)?;
     let mut supply = TOTAL_SUPPLY.load(deps.storage)?;
-    // TODO: this is just a safety assertion - do we keep it, or remove caching?
-    // in the end supply is just there to cache the (expected) results of get_bonded() so we don't
-    // have expensive queries everywhere
-    assert_bonds(&supply, bonded)?;
-    let unbond = remainder.multiply_ratio(bonded, supply.issued);
-    supply.bonded = bonded.checked_sub(unbond).map_err(StdError::overflow)?;
+    // catch up on any slashing before pricing the burnt tokens
+    let slashed = sync_bonded(&mut supply, bonded)?;
+    let value = remainder.multiply_ratio(bonded + supply.buffer, supply.issued);
+    let fee = value * invest.instant_unbond_fee;
+    let payout = value - fee;
+    if payout > supply.buffer {
+        return Err(ContractError::BufferTooSmall {
+            available: supply.buffer,
+        });
+    }
+    supply.buffer -= payout;
     supply.issued = supply
         .issued
         .checked_sub(remainder)
         .map_err(StdError::overflow)?;
-    supply.claims += unbond;
     TOTAL_SUPPLY.save(deps.storage, &supply)?;
+    record_rate(deps.storage, &env, &supply)?;
 
-    CLAIMS.create_claim(
-        deps.storage,
-        &info.sender,
-        unbond,
-        invest.unbonding_period.after(&env.block),
-    )?;
-
-    // unbond them
-    let res = Response::new()
-        .add_message(StakingMsg::Undelegate {
-            validator: invest.validator,
-            amount: coin(unbond.u128(), &invest.bond_denom),
-        })
-        .add_attribute("action", "unbond")
+    let mut res = Response::new();
+    if !payout.is_zero() {
+        res = res.add_message(BankMsg::Send {
+            to_address: info.sender.to_string(),
+            amount: coins(payout.u128(), &invest.bond_denom),
+        });
+    }
+    let res = res
+        .add_events(slashed)
+        .add_attribute("action", "instant_unbond")
         .add_attribute("to", info.sender)
-        .add_attribute("unbonded", unbond)
+        .add_attribute("paid", payout)
+        .add_attribute("fee", fee)
         .add_attribute("burnt", amount);
     Ok(res)
 }
@@ -295,17 +588,34 @@ pub fn claim(deps: DepsMut, env: Env, info: MessageInfo) -> Result<Response, Con
     let mut balance = deps
         .querier
         .query_balance(&env.contract.address, &invest.bond_denom)?;
+    // the buffer is not there to pay claims
+    let supply = TOTAL_SUPPLY.load(deps.storage)?;
+    balance.amount = balance.amount.saturating_sub(supply.buffer);
     if balance.amount < invest.min_withdrawal {
         return Err(ContractError::BalanceTooSmall {});
     }
 
     // check how much to send - min(balance, claims[sender]), and reduce the claim
     // Ensure we have enough balance to cover this and only send some claims if that is all we can cover
+    let before = CLAIMS.query_claims(deps.as_ref(), &info.sender)?.claims;
     let to_send =
         CLAIMS.claim_tokens(deps.storage, &info.sender, &env.block, Some(balance.amount))?;
     if to_send == Uint128::zero() {
         return Err(ContractError::NothingToClaim {});
     }
+    let after = CLAIMS.query_claims(deps.as_ref(), &info.sender)?.claims;
+    if after.is_empty() {
+        CLAIMANTS.remove(deps.storage, &info.sender);
+    }
+    // claim_tokens keeps the order of the claims it leaves, everything else was paid out
+    let mut left = after.iter().peekable();
+    for claim in before {
+        if left.peek() == Some(&&claim) {
+            left.next();
+        } else {
+            remove_unbonding(deps.storage, &claim.release_at, claim.amount)?;
+        }
+    }
 
     // update total supply (lower claim)
     TOTAL_SUPPLY.update(deps.storage, |mut supply| -> StdResult<_> {
@@ -328,25 +638,73 @@ pub fn claim(deps: DepsMut, env: Env, info: MessageInfo) -> Result<Response, Con
 
 /// reinvest will withdraw all pending rewards,
 /// then issue a callback to itself via _bond_all_tokens
-/// to reinvest the new earnings (and anything else that accumulated)
-pub fn reinvest(deps: DepsMut, env: Env, _info: MessageInfo) -> Result<Response, ContractError> {
-    let contract_addr = env.contract.address;
+/// to reinvest the new earnings (and anything else that accumulated).
+/// The caller is paid the keeper reward out of the withdrawn rewards
+pub fn reinvest(deps: DepsMut, env: Env, info: MessageInfo) -> Result<Response, ContractError> {
     let invest = INVESTMENT.load(deps.storage)?;
+    if let (Some(interval), Some(last)) = (
+        invest.reinvest_interval,
+        LAST_REINVEST.may_load(deps.storage)?,
+    ) {
+        let next = last.plus_seconds(interval);
+        if env.block.time < next {
+            return Err(ContractError::ReinvestTooSoon {
+                next: Expiration::AtTime(next),
+            });
+        }
+    }
+    LAST_REINVEST.save(deps.storage, &env.block.time)?;
+
+    let contract_addr = env.contract.address;
     let msg = to_binary(&ExecuteMsg::_BondAllTokens {})?;
 
-    // and bond them to the validator
-    let res = Response::new()
-        .add_message(DistributionMsg::WithdrawDelegatorReward {
-            validator: invest.validator,
-        })
+    // withdraw from every validator we have a delegation with
+    let delegations = deps.querier.query_all_delegations(&contract_addr)?;
+    let rewards = pending_rewards(&deps.querier, &contract_addr, &invest.bond_denom)?;
+    let withdraw = delegations
+        .into_iter()
+        .map(|d| DistributionMsg::WithdrawDelegatorReward {
+            validator: d.validator,
+        });
+
+    // pay the keeper once the rewards arrived, and bond the rest to the validators
+    let keeper_fee = rewards * invest.keeper_reward;
+    let mut res = Response::new().add_messages(withdraw);
+    if !keeper_fee.is_zero() {
+        res = res.add_message(BankMsg::Send {
+            to_address: info.sender.to_string(),
+            amount: coins(keeper_fee.u128(), &invest.bond_denom),
+        });
+    }
+    let res = res
         .add_message(WasmMsg::Execute {
             contract_addr: contract_addr.to_string(),
             msg,
             funds: vec![],
-        });
+        })
+        .add_attribute("action", "harvest")
+        .add_attribute("rewards", rewards)
+        .add_attribute("keeper", info.sender)
+        .add_attribute("keeper_reward", keeper_fee);
     Ok(res)
 }
 
+// pending_rewards sums the rewards accumulated on all our delegations
+fn pending_rewards(querier: &QuerierWrapper, contract: &Addr, denom: &str) -> StdResult<Uint128> {
+    let mut rewards = Uint128::zero();
+    for d in querier.query_all_delegations(contract)? {
+        if let Some(full) = querier.query_delegation(contract, d.validator)? {
+            rewards += full
+                .accumulated_rewards
+                .iter()
+                .filter(|c| c.denom == denom)
+                .map(|c| c.amount)
+                .sum::<Uint128>();
+        }
+    }
+    Ok(rewards)
+}
+
 pub fn _bond_all_tokens(
     deps: DepsMut,
     env: Env,
@@ -363,40 +721,296 @@ pub fn _bond_all_tokens(
         .querier
         .query_balance(&env.contract.address, &invest.bond_denom)?;
 
-    // we deduct pending claims from our account balance before reinvesting.
-    // if there is not enough funds, we just return a no-op
-    match TOTAL_SUPPLY.update(deps.storage, |mut supply| -> StdResult<_> {
-        balance.amount = balance.amount.checked_sub(supply.claims)?;
-        // this just triggers the "no op" case if we don't have min_withdrawal left to reinvest
-        balance.amount.checked_sub(invest.min_withdrawal)?;
-        supply.bonded += balance.amount;
-        Ok(supply)
-    }) {
-        Ok(_) => {}
-        // if it is below the minimum, we do a no-op (do not revert other state from withdrawal)
-        Err(StdError::Overflow { .. }) => return Ok(Response::default()),
-        Err(e) => return Err(ContractError::Std(e)),
+    // catch up on any slashing, even if there is nothing to reinvest
+    let bonded = get_bonded(&deps.querier, &env.contract.address)?;
+    let mut supply = TOTAL_SUPPLY.load(deps.storage)?;
+    let slashed = sync_bonded(&mut supply, bonded)?;
+    let res = Response::new().add_events(slashed);
+
+    // we deduct pending claims and the buffer from our account balance before reinvesting.
+    // if it is below the minimum, we do a no-op (do not revert other state from withdrawal)
+    let available = balance
+        .amount
+        .checked_sub(supply.claims + supply.buffer)
+        .ok()
+        .filter(|amount| *amount >= invest.min_withdrawal);
+    balance.amount = match available {
+        Some(amount) => amount,
+        None => {
+            TOTAL_SUPPLY.save(deps.storage, &supply)?;
+            record_rate(deps.storage, &env, &supply)?;
+            return Ok(res);
+        }
+    };
+    // top up the buffer first, then bond the rest
+    let to_buffer = buffer_top_up(&invest, &supply, balance.amount);
+    let to_bond = balance.amount - to_buffer;
+    supply.bonded += to_bond;
+    supply.buffer += to_buffer;
+    TOTAL_SUPPLY.save(deps.storage, &supply)?;
+    record_rate(deps.storage, &env, &supply)?;
+
+    // and bond them to the validators
+    let res = res
+        .add_messages(delegate_by_weight(&invest, to_bond))
+        .add_attribute("action", "reinvest")
+        .add_attribute("bonded", to_bond)
+        .add_attribute("buffered", to_buffer);
+    Ok(res)
+}
+
+/// rebalance redelegates from validators above their target weight to those below it.
+/// Only stake that can currently be redelegated is moved, so it may take several calls
+pub fn rebalance(deps: DepsMut, env: Env, info: MessageInfo) -> Result<Response, ContractError> {
+    let invest = INVESTMENT.load(deps.storage)?;
+    assert_owner(&invest, &info)?;
+
+    let contract_addr = env.contract.address;
+    let bonded = get_bonded(&deps.querier, &contract_addr)?;
+    let targets = split_by_weight(bonded, &invest.validators);
+    let delegations = deps.querier.query_all_delegations(&contract_addr)?;
+    let delegated_to = |validator: &str| {
+        delegations
+            .iter()
+            .find(|d| d.validator == validator)
+            .map(|d| d.amount.amount)
+            .unwrap_or_default()
+    };
+
+    // validators above their target, including any no longer in the set
+    let mut surplus = vec![];
+    for d in &delegations {
+        let target = targets
+            .iter()
+            .find(|(validator, _)| validator == &d.validator)
+            .map(|(_, target)| *target)
+            .unwrap_or_default();
+        if d.amount.amount > target {
+            let movable = deps
+                .querier
+                .query_delegation(&contract_addr, &d.validator)?
+                .map(|full| full.can_redelegate.amount)
+                .unwrap_or_default();
+            surplus.push((d.validator.clone(), (d.amount.amount - target).min(movable)));
+        }
+    }
+    // validators below their target
+    let mut deficit: Vec<_> = targets
+        .into_iter()
+        .filter_map(|(validator, target)| {
+            let current = delegated_to(&validator);
+            (target > current).then(|| (validator, target - current))
+        })
+        .collect();
+
+    let mut redelegate = vec![];
+    let mut deficits = deficit.iter_mut();
+    let mut next = deficits.next();
+    for (src, mut amount) in surplus {
+        while let Some((dst, missing)) = next.as_mut() {
+            if amount.is_zero() {
+                break;
+            }
+            let moved = amount.min(*missing);
+            redelegate.push(StakingMsg::Redelegate {
+                src_validator: src.clone(),
+                dst_validator: dst.clone(),
+                amount: coin(moved.u128(), &invest.bond_denom),
+            });
+            amount -= moved;
+            *missing -= moved;
+            if missing.is_zero() {
+                next = deficits.next();
+            }
+        }
     }
 
-    // and bond them to the validator
     let res = Response::new()
-        .add_message(StakingMsg::Delegate {
-            validator: invest.validator,
-            amount: balance.clone(),
-        })
-        .add_attribute("action", "reinvest")
-        .add_attribute("bonded", balance.amount);
+        .add_attribute("action", "rebalance")
+        .add_attribute("redelegations", redelegate.len().to_string())
+        .add_messages(redelegate);
+    Ok(res)
+}
+
+fn assert_owner(invest: &InvestmentInfo, info: &MessageInfo) -> Result<(), ContractError> {
+    if info.sender != invest.owner {
+        Err(ContractError::Unauthorized {})
+    } else {
+        Ok(())
+    }
+}
+
+/// The fields of `ExecuteMsg::UpdateConfig`, None keeps the current value
+#[derive(Default)]
+pub struct ConfigUpdate {
+    pub exit_tax: Option<Decimal>,
+    pub min_withdrawal: Option<Uint128>,
+    pub keeper_reward: Option<Decimal>,
+    pub reinvest_interval: Option<u64>,
+    pub buffer_ratio: Option<Decimal>,
+    pub instant_unbond_fee: Option<Decimal>,
+}
+
+pub fn update_config(
+    deps: DepsMut,
+    info: MessageInfo,
+    update: ConfigUpdate,
+) -> Result<Response, ContractError> {
+    let mut invest = INVESTMENT.load(deps.storage)?;
+    assert_owner(&invest, &info)?;
+
+    if let Some(exit_tax) = update.exit_tax {
+        if exit_tax > Decimal::one() {
+            return Err(ContractError::InvalidExitTax {});
+        }
+        invest.exit_tax = exit_tax;
+    }
+    if let Some(min_withdrawal) = update.min_withdrawal {
+        invest.min_withdrawal = min_withdrawal;
+    }
+    if let Some(keeper_reward) = update.keeper_reward {
+        if keeper_reward > Decimal::one() {
+            return Err(ContractError::InvalidKeeperReward {});
+        }
+        invest.keeper_reward = keeper_reward;
+    }
+    if let Some(reinvest_interval) = update.reinvest_interval {
+        // a zero interval lifts the limit
+        invest.reinvest_interval = Some(reinvest_interval).filter(|seconds| *seconds > 0);
+    }
+    if let Some(buffer_ratio) = update.buffer_ratio {
+        if buffer_ratio > Decimal::one() {
+            return Err(ContractError::InvalidBufferRatio {});
+        }
+        invest.buffer_ratio = buffer_ratio;
+    }
+    if let Some(instant_unbond_fee) = update.instant_unbond_fee {
+        if instant_unbond_fee > Decimal::one() {
+            return Err(ContractError::InvalidInstantUnbondFee {});
+        }
+        invest.instant_unbond_fee = instant_unbond_fee;
+    }
+    INVESTMENT.save(deps.storage, &invest)?;
+
+    let res = Response::new()
+        .add_attribute("action", "update_config")
+        .add_attribute("exit_tax", invest.exit_tax.to_string())
+        .add_attribute("min_withdrawal", invest.min_withdrawal)
+        .add_attribute("keeper_reward", invest.keeper_reward.to_string())
+        .add_attribute("buffer_ratio", invest.buffer_ratio.to_string())
+        .add_attribute("instant_unbond_fee", invest.instant_unbond_fee.to_string());
+    Ok(res)
+}
+
+pub fn propose_owner(
+    deps: DepsMut,
+    info: MessageInfo,
+    owner: String,
+) -> Result<Response, ContractError> {
+    let invest = INVESTMENT.load(deps.storage)?;
+    assert_owner(&invest, &info)?;
+
+    let owner = deps.api.addr_validate(&owner)?;
+    PENDING_OWNER.save(deps.storage, &owner)?;
+
+    let res = Response::new()
+        .add_attribute("action", "propose_owner")
+        .add_attribute("owner", owner);
+    Ok(res)
+}
+
+pub fn accept_ownership(deps: DepsMut, info: MessageInfo) -> Result<Response, ContractError> {
+    let pending = PENDING_OWNER
+        .may_load(deps.storage)?
+        .ok_or(ContractError::NoPendingOwner {})?;
+    if info.sender != pending {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    INVESTMENT.update(deps.storage, |mut invest| -> StdResult<_> {
+        invest.owner = pending;
+        Ok(invest)
+    })?;
+    PENDING_OWNER.remove(deps.storage);
+
+    let res = Response::new()
+        .add_attribute("action", "accept_ownership")
+        .add_attribute("owner", info.sender);
     Ok(res)
 }
 
+/// change_validator swaps one validator of the set for another and moves all its stake over
+pub fn change_validator(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    from: String,
+    to: String,
+) -> Result<Response, ContractError> {
+    let mut invest = INVESTMENT.load(deps.storage)?;
+    assert_owner(&invest, &info)?;
+
+    if invest.validators.iter().any(|v| v.address == to) {
+        return Err(ContractError::DuplicateValidator { validator: to });
+    }
+    if deps.querier.query_validator(&to)?.is_none() {
+        return Err(ContractError::NotInValidatorSet { validator: to });
+    }
+    let validator = invest
+        .validators
+        .iter_mut()
+        .find(|v| v.address == from)
+        .ok_or_else(|| ContractError::UnknownValidator {
+            validator: from.clone(),
+        })?;
+    validator.address = to.clone();
+
+    // stake that was itself redelegated to `from` cannot move again until it matured
+    let mut res = Response::new();
+    if let Some(delegation) = deps
+        .querier
+        .query_delegation(&env.contract.address, &from)?
+    {
+        if delegation.can_redelegate != delegation.amount {
+            return Err(ContractError::RedelegationInProgress { validator: from });
+        }
+        if !delegation.amount.amount.is_zero() {
+            res = res.add_message(StakingMsg::Redelegate {
+                src_validator: from.clone(),
+                dst_validator: to.clone(),
+                amount: delegation.amount,
+            });
+        }
+    }
+    INVESTMENT.save(deps.storage, &invest)?;
+
+    Ok(res
+        .add_attribute("action", "change_validator")
+        .add_attribute("from", from)
+        .add_attribute("to", to))
+}
+
 #[cfg_attr(not(feature = "library"), entry_point)]
-pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> StdResult<Binary> {
+pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> StdResult<Binary> {
     match msg {
         // custom queries
         QueryMsg::Claims { address } => {
             to_binary(&CLAIMS.query_claims(deps, &deps.api.addr_validate(&address)?)?)
         }
-        QueryMsg::Investment {} => to_binary(&query_investment(deps)?),
+        QueryMsg::AllClaims { start_after, limit } => {
+            to_binary(&query_all_claims(deps, start_after, limit)?)
+        }
+        QueryMsg::UnbondingSchedule { start_after, limit } => {
+            to_binary(&query_unbonding_schedule(deps, start_after, limit)?)
+        }
+        QueryMsg::Investment {} => to_binary(&query_investment(deps, env)?),
+        QueryMsg::ExchangeRate {} => to_binary(&query_exchange_rate(deps, env)?),
+        QueryMsg::TimeWeightedRate { window } => {
+            to_binary(&query_time_weighted_rate(deps, env, window)?)
+        }
+        QueryMsg::RateHistory { start_after, limit } => {
+            to_binary(&query_rate_history(deps, start_after, limit)?)
+        }
         // inherited from cw20-base
         QueryMsg::TokenInfo {} => to_binary(&query_token_info(deps)?),
         QueryMsg::Balance { address } => to_binary(&query_balance(deps, address)?),
@@ -406,37 +1020,209 @@ pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> StdResult<Binary> {
     }
 }
 
-pub fn query_investment(deps: Deps) -> StdResult<InvestmentResponse> {
+pub fn query_all_claims(
+    deps: Deps,
+    start_after: Option<String>,
+    limit: Option<u32>,
+) -> StdResult<AllClaimsResponse> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start = start_after
+        .map(|addr| deps.api.addr_validate(&addr))
+        .transpose()?;
+    let claims = CLAIMANTS
+        .keys(
+            deps.storage,
+            start.as_ref().map(Bound::exclusive),
+            None,
+            Order::Ascending,
+        )
+        .take(limit)
+        .map(|address| {
+            let address = address?;
+            let claims = CLAIMS.query_claims(deps, &address)?.claims;
+            Ok(AddressClaims {
+                address: address.into(),
+                claims,
+            })
+        })
+        .collect::<StdResult<_>>()?;
+    Ok(AllClaimsResponse { claims })
+}
+
+pub fn query_unbonding_schedule(
+    deps: Deps,
+    start_after: Option<u64>,
+    limit: Option<u32>,
+) -> StdResult<UnbondingScheduleResponse> {
+    let invest = INVESTMENT.load(deps.storage)?;
+    let supply = TOTAL_SUPPLY.load(deps.storage)?;
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let unbonding = UNBONDING
+        .range(
+            deps.storage,
+            start_after.map(Bound::exclusive),
+            None,
+            Order::Ascending,
+        )
+        .take(limit)
+        .map(|item| {
+            let (release, amount) = item?;
+            let release_at = match invest.unbonding_period {
+                Duration::Height(_) => Expiration::AtHeight(release),
+                Duration::Time(_) => Expiration::AtTime(Timestamp::from_seconds(release)),
+            };
+            Ok(UnbondingEntry { release_at, amount })
+        })
+        .collect::<StdResult<_>>()?;
+    Ok(UnbondingScheduleResponse {
+        total: supply.claims,
+        unbonding,
+    })
+}
+
+pub fn query_exchange_rate(deps: Deps, env: Env) -> StdResult<ExchangeRateResponse> {
+    let invest = INVESTMENT.load(deps.storage)?;
+    let supply = TOTAL_SUPPLY.load(deps.storage)?;
+    let rewards = pending_rewards(&deps.querier, &env.contract.address, &invest.bond_denom)?;
+    // the keeper takes their cut before the rest is bonded
+    let rewards = rewards - rewards * invest.keeper_reward;
+    let rate = if supply.issued.is_zero() {
+        FALLBACK_RATIO
+    } else {
+        Decimal::from_ratio(supply.bonded + supply.buffer + rewards, supply.issued)
+    };
+    Ok(ExchangeRateResponse {
+        rate,
+        rate_without_rewards: stored_rate(&supply),
+    })
+}
+
+pub fn query_time_weighted_rate(
+    deps: Deps,
+    env: Env,
+    window: u64,
+) -> StdResult<TimeWeightedRateResponse> {
+    let end = env.block.time.seconds();
+    let first = RATE_HISTORY
+        .keys(deps.storage, None, None, Order::Ascending)
+        .next()
+        .transpose()?
+        .ok_or_else(|| StdError::not_found("exchange rate history"))?;
+    let start = end.saturating_sub(window).max(first).min(end);
+
+    let cumulative_end = cumulative_rate(deps.storage, end)?.unwrap_or_default();
+    let rate = if start == end {
+        // no time passed, so the average is the latest rate
+        RATE_HISTORY
+            .range(deps.storage, None, None, Order::Descending)
+            .next()
+            .transpose()?
+            .map(|(_, obs)| obs.rate)
+            .unwrap_or(FALLBACK_RATIO)
+    } else {
+        let cumulative_start = cumulative_rate(deps.storage, start)?.unwrap_or_default();
+        let average = (cumulative_end - cumulative_start) / Uint256::from(end - start);
+        Decimal::new(Uint128::try_from(average)?)
+    };
+    Ok(TimeWeightedRateResponse {
+        rate,
+        start: Timestamp::from_seconds(start),
+        end: Timestamp::from_seconds(end),
+    })
+}
+
+pub fn query_rate_history(
+    deps: Deps,
+    start_after: Option<u64>,
+    limit: Option<u32>,
+) -> StdResult<RateHistoryResponse> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let rates = RATE_HISTORY
+        .range(
+            deps.storage,
+            start_after.map(Bound::exclusive),
+            None,
+            Order::Ascending,
+        )
+        .take(limit)
+        .map(|item| {
+            item.map(|(time, obs)| RateRecord {
+                time: Timestamp::from_seconds(time),
+                rate: obs.rate,
+            })
+        })
+        .collect::<StdResult<_>>()?;
+    Ok(RateHistoryResponse { rates })
+}
+
+pub fn query_investment(deps: Deps, env: Env) -> StdResult<InvestmentResponse> {
     let invest = INVESTMENT.load(deps.storage)?;
     let supply = TOTAL_SUPPLY.load(deps.storage)?;
+    let pending_owner = PENDING_OWNER.may_load(deps.storage)?;
+    let last_reinvest = LAST_REINVEST.may_load(deps.storage)?;
+    let rewards = pending_rewards(&deps.querier, &env.contract.address, &invest.bond_denom)?;
 
     let res = InvestmentResponse {
         owner: invest.owner.to_string(),
+        pending_owner: pending_owner.map(Addr::into_string),
         exit_tax: invest.exit_tax,
-        validator: invest.validator,
+        validators: invest.validators,
         min_withdrawal: invest.min_withdrawal,
+        keeper_reward: invest.keeper_reward,
+        reinvest_interval: invest.reinvest_interval,
+        last_reinvest,
+        pending_rewards: coin(rewards.u128(), &invest.bond_denom),
+        buffer_ratio: invest.buffer_ratio,
+        instant_unbond_fee: invest.instant_unbond_fee,
         token_supply: supply.issued,
         staked_tokens: coin(supply.bonded.u128(), &invest.bond_denom),
-        nominal_value: if supply.issued.is_zero() {
-            FALLBACK_RATIO
-        } else {
-            Decimal::from_ratio(supply.bonded, supply.issued)
-        },
+        buffer: coin(supply.buffer.u128(), &invest.bond_denom),
+        nominal_value: stored_rate(&supply),
     };
     Ok(res)
 }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use std::str::FromStr;
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn migrate(mut deps: DepsMut, _env: Env, msg: MigrateMsg) -> Result<Response, ContractError> {
+    let contract_info = get_contract_version(deps.storage)?;
+    if contract_info.contract != CONTRACT_NAME {
+        return Err(ContractError::CannotMigrate {
+            previous_contract: contract_info.contract,
+        });
+    }
+    let contract_version: Version = contract_info.version.parse()?;
+    let current_version: Version = CONTRACT_VERSION.parse()?;
+    if contract_version > current_version {
+        return Err(ContractError::CannotMigrateVersion {
+            previous_version: contract_info.version,
+        });
+    }
+
+    set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
+    let mut res = Response::new().add_attribute("method", "migrate");
+    if contract_version <= Version::new(0, 14, 2) {
+        v0_14_2::migrate_investment(deps.branch())?;
+    }
+    // there may be too many claims to index them all in one go
+    if v0_14_2::indexing_claims(deps.storage)? {
+        let limit = msg.limit.unwrap_or(DEFAULT_MIGRATE_LIMIT);
+        let done = v0_14_2::index_claims(deps, limit)?;
+        res = res.add_attribute("claims_indexed", done.to_string());
+    }
+    Ok(res)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::str::FromStr;
 
     use cosmwasm_std::testing::{
         mock_dependencies, mock_env, mock_info, MockQuerier, MOCK_CONTRACT_ADDR,
     };
     use cosmwasm_std::{
-        coins, Coin, CosmosMsg, Decimal, FullDelegation, OverflowError, OverflowOperation,
-        Validator,
+        attr, coins, Coin, CosmosMsg, Decimal, FullDelegation, OverflowError, OverflowOperation,
+        SubMsg, Validator,
     };
     use cw_controllers::Claim;
     use cw_utils::{Duration, DAY, HOUR, WEEK};
@@ -474,6 +1260,19 @@ mod tests {
         );
     }
 
+    fn set_delegations(querier: &mut MockQuerier, delegations: &[(&str, u128)]) {
+        let validators: Vec<_> = delegations
+            .iter()
+            .map(|(addr, _)| sample_validator(addr))
+            .collect();
+        let delegations: Vec<_> = delegations
+            .iter()
+            .filter(|(_, amount)| *amount > 0)
+            .map(|(addr, amount)| sample_delegation(addr, coin(*amount, "ustake")))
+            .collect();
+        querier.update_staking("ustake", &validators, &delegations);
+    }
+
     // just a test helper, forgive the panic
     fn later(env: &Env, delta: Duration) -> Env {
         let time_delta = match delta {
@@ -492,10 +1291,17 @@ mod tests {
             name: "Cool Derivative".to_string(),
             symbol: "DRV".to_string(),
             decimals: 9,
-            validator: String::from(DEFAULT_VALIDATOR),
+            validators: vec![ValidatorWeight {
+                address: String::from(DEFAULT_VALIDATOR),
+                weight: 1,
+            }],
             unbonding_period: DAY * 3,
             exit_tax: Decimal::percent(tax_percent),
             min_withdrawal: Uint128::new(min_withdrawal),
+            keeper_reward: Decimal::zero(),
+            reinvest_interval: None,
+            buffer_ratio: Decimal::zero(),
+            instant_unbond_fee: Decimal::zero(),
         }
     }
 
@@ -521,10 +1327,17 @@ mod tests {
             name: "Cool Derivative".to_string(),
             symbol: "DRV".to_string(),
             decimals: 9,
-            validator: String::from("my-validator"),
+            validators: vec![ValidatorWeight {
+                address: String::from("my-validator"),
+                weight: 1,
+            }],
             unbonding_period: WEEK,
             exit_tax: Decimal::percent(2),
             min_withdrawal: Uint128::new(50),
+            keeper_reward: Decimal::zero(),
+            reinvest_interval: None,
+            buffer_ratio: Decimal::zero(),
+            instant_unbond_fee: Decimal::zero(),
         };
         let info = mock_info(&creator, &[]);
 
@@ -556,10 +1369,17 @@ mod tests {
             name: "Cool Derivative".to_string(),
             symbol: "DRV".to_string(),
             decimals: 0,
-            validator: String::from("my-validator"),
+            validators: vec![ValidatorWeight {
+                address: String::from("my-validator"),
+                weight: 1,
+            }],
             unbonding_period: HOUR * 12,
             exit_tax: Decimal::percent(2),
             min_withdrawal: Uint128::new(50),
+            keeper_reward: Decimal::zero(),
+            reinvest_interval: None,
+            buffer_ratio: Decimal::zero(),
+            instant_unbond_fee: Decimal::zero(),
         };
         let info = mock_info(&creator, &[]);
 
@@ -580,9 +1400,9 @@ mod tests {
         assert_eq!(get_claims(deps.as_ref(), &creator), vec![]);
 
         // investment info correct
-        let invest = query_investment(deps.as_ref()).unwrap();
+        let invest = query_investment(deps.as_ref(), mock_env()).unwrap();
         assert_eq!(&invest.owner, &creator);
-        assert_eq!(&invest.validator, &msg.validator);
+        assert_eq!(&invest.validators, &msg.validators);
         assert_eq!(invest.exit_tax, msg.exit_tax);
         assert_eq!(invest.min_withdrawal, msg.min_withdrawal);
 
@@ -625,7 +1445,7 @@ mod tests {
         assert_eq!(get_balance(deps.as_ref(), &bob), Uint128::new(1000));
 
         // investment info correct (updated supply)
-        let invest = query_investment(deps.as_ref()).unwrap();
+        let invest = query_investment(deps.as_ref(), mock_env()).unwrap();
         assert_eq!(invest.token_supply, Uint128::new(1000));
         assert_eq!(invest.staked_tokens, coin(1000, "ustake"));
         assert_eq!(invest.nominal_value, Decimal::one());
@@ -669,7 +1489,7 @@ mod tests {
         set_delegation(&mut deps.querier, 1500, "ustake");
 
         // we should now see 1000 issues and 1500 bonded (and a price of 1.5)
-        let invest = query_investment(deps.as_ref()).unwrap();
+        let invest = query_investment(deps.as_ref(), mock_env()).unwrap();
         assert_eq!(invest.token_supply, Uint128::new(1000));
         assert_eq!(invest.staked_tokens, coin(1500, "ustake"));
         let ratio = Decimal::from_str("1.5").unwrap();
@@ -688,7 +1508,7 @@ mod tests {
         // alice should have gotten 2000 DRV for the 3000 stake, keeping the ratio at 1.5
         assert_eq!(get_balance(deps.as_ref(), &alice), Uint128::new(2000));
 
-        let invest = query_investment(deps.as_ref()).unwrap();
+        let invest = query_investment(deps.as_ref(), mock_env()).unwrap();
         assert_eq!(invest.token_supply, Uint128::new(3000));
         assert_eq!(invest.staked_tokens, coin(4500, "ustake"));
         assert_eq!(invest.nominal_value, ratio);
@@ -810,7 +1630,7 @@ mod tests {
         // supplies updated, ratio the same (1.5)
         let ratio = Decimal::from_str("1.5").unwrap();
 
-        let invest = query_investment(deps.as_ref()).unwrap();
+        let invest = query_investment(deps.as_ref(), mock_env()).unwrap();
         assert_eq!(invest.token_supply, bobs_balance + owner_cut);
         assert_eq!(invest.staked_tokens, coin(690, "ustake")); // 1500 - 810
         assert_eq!(invest.nominal_value, ratio);
@@ -962,4 +1782,898 @@ mod tests {
         execute(deps.as_mut(), mock_env(), bob_info, burn).unwrap();
         assert_eq!(get_balance(deps.as_ref(), &bob), Uint128::new(420));
     }
+
+    fn weighted_instantiate(validators: &[(&str, u64)]) -> InstantiateMsg {
+        InstantiateMsg {
+            validators: validators
+                .iter()
+                .map(|(address, weight)| ValidatorWeight {
+                    address: address.to_string(),
+                    weight: *weight,
+                })
+                .collect(),
+            ..default_instantiate(0, 50)
+        }
+    }
+
+    #[test]
+    fn migrate_single_validator_investment() {
+        let mut deps = mock_dependencies();
+        set_contract_version(&mut deps.storage, CONTRACT_NAME, "0.14.1").unwrap();
+        deps.storage.set(
+            b"invest",
+            br#"{"owner":"creator","bond_denom":"ustake","unbonding_period":{"time":604800},"exit_tax":"0.02","validator":"my-validator","min_withdrawal":"50"}"#,
+        );
+        assert!(INVESTMENT.load(&deps.storage).is_err());
+        // claims from before they were indexed
+        let release_at = WEEK.after(&mock_env().block);
+        for who in ["bob", "alice", "carl"] {
+            CLAIMS
+                .create_claim(
+                    &mut deps.storage,
+                    &Addr::unchecked(who),
+                    Uint128::new(100),
+                    release_at,
+                )
+                .unwrap();
+        }
+
+        // they are indexed a few addresses at a time
+        let res = migrate(deps.as_mut(), mock_env(), MigrateMsg { limit: Some(2) }).unwrap();
+        assert_eq!(res.attributes[1], attr("claims_indexed", "false"));
+        let all = query_all_claims(deps.as_ref(), None, None).unwrap();
+        assert_eq!(
+            all.claims,
+            vec![
+                AddressClaims {
+                    address: "alice".into(),
+                    claims: vec![Claim::new(100, release_at)],
+                },
+                AddressClaims {
+                    address: "bob".into(),
+                    claims: vec![Claim::new(100, release_at)],
+                }
+            ]
+        );
+        let res = migrate(deps.as_mut(), mock_env(), MigrateMsg { limit: Some(2) }).unwrap();
+        assert_eq!(res.attributes[1], attr("claims_indexed", "true"));
+        let all = query_all_claims(deps.as_ref(), None, None).unwrap();
+        assert_eq!(all.claims.len(), 3);
+        assert_eq!(all.claims[2].address, "carl");
+        // and summed up for the unbonding schedule
+        let release = mock_env().block.time.seconds() + 604_800;
+        let unbonding = UNBONDING.load(&deps.storage, release).unwrap();
+        assert_eq!(unbonding, Uint128::new(300));
+
+        let invest = INVESTMENT.load(&deps.storage).unwrap();
+        assert_eq!(
+            invest,
+            InvestmentInfo {
+                owner: Addr::unchecked("creator"),
+                bond_denom: "ustake".to_string(),
+                unbonding_period: WEEK,
+                exit_tax: Decimal::percent(2),
+                validators: vec![ValidatorWeight {
+                    address: "my-validator".to_string(),
+                    weight: 1,
+                }],
+                min_withdrawal: Uint128::new(50),
+                keeper_reward: Decimal::zero(),
+                reinvest_interval: None,
+                buffer_ratio: Decimal::zero(),
+                instant_unbond_fee: Decimal::zero(),
+            }
+        );
+        let version = get_contract_version(&deps.storage).unwrap();
+        assert_eq!(version.version, CONTRACT_VERSION);
+
+        // migrating again leaves the current format alone
+        let res = migrate(deps.as_mut(), mock_env(), MigrateMsg { limit: None }).unwrap();
+        assert_eq!(res.attributes.len(), 1);
+        assert_eq!(INVESTMENT.load(&deps.storage).unwrap(), invest);
+
+        // cannot migrate from another contract
+        set_contract_version(&mut deps.storage, "crates.io:cw20-base", "0.16.0").unwrap();
+        let err = migrate(deps.as_mut(), mock_env(), MigrateMsg { limit: None }).unwrap_err();
+        assert_eq!(
+            err,
+            ContractError::CannotMigrate {
+                previous_contract: "crates.io:cw20-base".to_string()
+            }
+        );
+
+        // cannot migrate from a newer version
+        set_contract_version(&mut deps.storage, CONTRACT_NAME, "9.0.0").unwrap();
+        let err = migrate(deps.as_mut(), mock_env(), MigrateMsg { limit: None }).unwrap_err();
+        assert_eq!(
+            err,
+            ContractError::CannotMigrateVersion {
+                previous_version: "9.0.0".to_string()
+            }
+        );
+    }
+
+    #[test]
+    fn instantiation_checks_weights() {
+        let mut deps = mock_dependencies();
+        set_delegations(&mut deps.querier, &[("alpha", 0), ("beta", 0)]);
+        let info = mock_info("creator", &[]);
+
+        let msg = weighted_instantiate(&[]);
+        let err = instantiate(deps.as_mut(), mock_env(), info.clone(), msg).unwrap_err();
+        assert_eq!(err, ContractError::NoValidators {});
+
+        let msg = weighted_instantiate(&[("alpha", 1), ("beta", 0)]);
+        let err = instantiate(deps.as_mut(), mock_env(), info.clone(), msg).unwrap_err();
+        assert_eq!(
+            err,
+            ContractError::ZeroWeight {
+                validator: "beta".into()
+            }
+        );
+
+        let msg = weighted_instantiate(&[("alpha", 1), ("beta", 2), ("alpha", 3)]);
+        let err = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap_err();
+        assert_eq!(
+            err,
+            ContractError::DuplicateValidator {
+                validator: "alpha".into()
+            }
+        );
+    }
+
+    #[test]
+    fn bonding_splits_by_weight() {
+        let mut deps = mock_dependencies();
+        set_delegations(&mut deps.querier, &[("alpha", 0), ("beta", 0)]);
+        let msg = weighted_instantiate(&[("alpha", 1), ("beta", 2)]);
+        instantiate(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg).unwrap();
+
+        // one third to alpha (plus rounding), two thirds to beta
+        let info = mock_info("bob", &coins(1000, "ustake"));
+        let res = execute(deps.as_mut(), mock_env(), info, ExecuteMsg::Bond {}).unwrap();
+        assert_eq!(
+            res.messages,
+            vec![
+                SubMsg::new(StakingMsg::Delegate {
+                    validator: "alpha".into(),
+                    amount: coin(334, "ustake"),
+                }),
+                SubMsg::new(StakingMsg::Delegate {
+                    validator: "beta".into(),
+                    amount: coin(666, "ustake"),
+                }),
+            ]
+        );
+        assert_eq!(get_balance(deps.as_ref(), "bob"), Uint128::new(1000));
+
+        // reinvesting withdraws from both and splits the same way
+        set_delegations(&mut deps.querier, &[("alpha", 334), ("beta", 666)]);
+        let info = mock_info("anyone", &[]);
+        let res = execute(deps.as_mut(), mock_env(), info, ExecuteMsg::Reinvest {}).unwrap();
+        assert_eq!(3, res.messages.len());
+        assert_eq!(
+            res.messages[0],
+            SubMsg::new(DistributionMsg::WithdrawDelegatorReward {
+                validator: "alpha".into()
+            })
+        );
+        deps.querier
+            .update_balance(MOCK_CONTRACT_ADDR, coins(300, "ustake"));
+        let info = mock_info(MOCK_CONTRACT_ADDR, &[]);
+        let res = execute(
+            deps.as_mut(),
+            mock_env(),
+            info,
+            ExecuteMsg::_BondAllTokens {},
+        )
+        .unwrap();
+        assert_eq!(
+            res.messages,
+            vec![
+                SubMsg::new(StakingMsg::Delegate {
+                    validator: "alpha".into(),
+                    amount: coin(100, "ustake"),
+                }),
+                SubMsg::new(StakingMsg::Delegate {
+                    validator: "beta".into(),
+                    amount: coin(200, "ustake"),
+                }),
+            ]
+        );
+    }
+
+    #[test]
+    fn unbonding_is_proportional() {
+        let mut deps = mock_dependencies();
+        set_delegations(&mut deps.querier, &[("alpha", 0), ("beta", 0)]);
+        let msg = weighted_instantiate(&[("alpha", 1), ("beta", 1)]);
+        instantiate(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg).unwrap();
+        let info = mock_info("bob", &coins(1000, "ustake"));
+        execute(deps.as_mut(), mock_env(), info, ExecuteMsg::Bond {}).unwrap();
+
+        // the split drifted, unbonding follows the actual delegations
+        set_delegations(&mut deps.querier, &[("alpha", 301), ("beta", 699)]);
+        let unbond = ExecuteMsg::Unbond {
+            amount: Uint128::new(100),
+        };
+        let res = execute(deps.as_mut(), mock_env(), mock_info("bob", &[]), unbond).unwrap();
+        assert_eq!(
+            res.messages,
+            vec![
+                SubMsg::new(StakingMsg::Undelegate {
+                    validator: "alpha".into(),
+                    amount: coin(31, "ustake"),
+                }),
+                SubMsg::new(StakingMsg::Undelegate {
+                    validator: "beta".into(),
+                    amount: coin(69, "ustake"),
+                }),
+            ]
+        );
+    }
+
+    #[test]
+    fn rebalance_restores_weights() {
+        let mut deps = mock_dependencies();
+        set_delegations(
+            &mut deps.querier,
+            &[("alpha", 0), ("beta", 0), ("gamma", 0)],
+        );
+        let msg = weighted_instantiate(&[("alpha", 1), ("beta", 2), ("gamma", 1)]);
+        instantiate(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg).unwrap();
+        let info = mock_info("bob", &coins(1000, "ustake"));
+        execute(deps.as_mut(), mock_env(), info, ExecuteMsg::Bond {}).unwrap();
+
+        // everything ended up on alpha
+        set_delegations(
+            &mut deps.querier,
+            &[("alpha", 1000), ("beta", 0), ("gamma", 0)],
+        );
+
+        // only the owner may rebalance
+        let info = mock_info("bob", &[]);
+        let err = execute(deps.as_mut(), mock_env(), info, ExecuteMsg::Rebalance {}).unwrap_err();
+        assert_eq!(err, ContractError::Unauthorized {});
+
+        let info = mock_info("creator", &[]);
+        let res = execute(deps.as_mut(), mock_env(), info, ExecuteMsg::Rebalance {}).unwrap();
+        assert_eq!(
+            res.messages,
+            vec![
+                SubMsg::new(StakingMsg::Redelegate {
+                    src_validator: "alpha".into(),
+                    dst_validator: "beta".into(),
+                    amount: coin(500, "ustake"),
+                }),
+                SubMsg::new(StakingMsg::Redelegate {
+                    src_validator: "alpha".into(),
+                    dst_validator: "gamma".into(),
+                    amount: coin(250, "ustake"),
+                }),
+            ]
+        );
+
+        // once balanced, there is nothing to do
+        set_delegations(
+            &mut deps.querier,
+            &[("alpha", 250), ("beta", 500), ("gamma", 250)],
+        );
+        let info = mock_info("creator", &[]);
+        let res = execute(deps.as_mut(), mock_env(), info, ExecuteMsg::Rebalance {}).unwrap();
+        assert_eq!(0, res.messages.len());
+    }
+
+    #[test]
+    fn owner_updates_config() {
+        let mut deps = mock_dependencies();
+        set_validator(&mut deps.querier);
+        let info = mock_info("creator", &[]);
+        instantiate(deps.as_mut(), mock_env(), info, default_instantiate(2, 50)).unwrap();
+
+        let update = ExecuteMsg::UpdateConfig {
+            exit_tax: Some(Decimal::percent(5)),
+            min_withdrawal: None,
+            keeper_reward: None,
+            reinvest_interval: None,
+            buffer_ratio: None,
+            instant_unbond_fee: None,
+        };
+        let info = mock_info("bob", &[]);
+        let err = execute(deps.as_mut(), mock_env(), info, update.clone()).unwrap_err();
+        assert_eq!(err, ContractError::Unauthorized {});
+        let info = mock_info("creator", &[]);
+        execute(deps.as_mut(), mock_env(), info, update).unwrap();
+
+        let invest = query_investment(deps.as_ref(), mock_env()).unwrap();
+        assert_eq!(invest.exit_tax, Decimal::percent(5));
+        assert_eq!(invest.min_withdrawal, Uint128::new(50));
+
+        let update = ExecuteMsg::UpdateConfig {
+            exit_tax: Some(Decimal::percent(101)),
+            min_withdrawal: Some(Uint128::new(10)),
+            keeper_reward: None,
+            reinvest_interval: None,
+            buffer_ratio: None,
+            instant_unbond_fee: None,
+        };
+        let info = mock_info("creator", &[]);
+        let err = execute(deps.as_mut(), mock_env(), info, update).unwrap_err();
+        assert_eq!(err, ContractError::InvalidExitTax {});
+    }
+
+    #[test]
+    fn ownership_transfer_takes_two_steps() {
+        let mut deps = mock_dependencies();
+        set_validator(&mut deps.querier);
+        let info = mock_info("creator", &[]);
+        instantiate(deps.as_mut(), mock_env(), info, default_instantiate(2, 50)).unwrap();
+
+        let info = mock_info("alice", &[]);
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            info,
+            ExecuteMsg::AcceptOwnership {},
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::NoPendingOwner {});
+
+        let propose = ExecuteMsg::ProposeOwner {
+            owner: "alice".to_string(),
+        };
+        let info = mock_info("bob", &[]);
+        let err = execute(deps.as_mut(), mock_env(), info, propose.clone()).unwrap_err();
+        assert_eq!(err, ContractError::Unauthorized {});
+        let info = mock_info("creator", &[]);
+        execute(deps.as_mut(), mock_env(), info, propose).unwrap();
+
+        // nothing changes until alice accepts
+        let invest = query_investment(deps.as_ref(), mock_env()).unwrap();
+        assert_eq!(invest.owner, "creator");
+        assert_eq!(invest.pending_owner, Some("alice".to_string()));
+        let info = mock_info("bob", &[]);
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            info,
+            ExecuteMsg::AcceptOwnership {},
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::Unauthorized {});
+
+        let info = mock_info("alice", &[]);
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            info,
+            ExecuteMsg::AcceptOwnership {},
+        )
+        .unwrap();
+        let invest = query_investment(deps.as_ref(), mock_env()).unwrap();
+        assert_eq!(invest.owner, "alice");
+        assert_eq!(invest.pending_owner, None);
+
+        // the old owner lost their rights
+        let info = mock_info("creator", &[]);
+        let err = execute(deps.as_mut(), mock_env(), info, ExecuteMsg::Rebalance {}).unwrap_err();
+        assert_eq!(err, ContractError::Unauthorized {});
+    }
+
+    #[test]
+    fn change_validator_redelegates_everything() {
+        let mut deps = mock_dependencies();
+        set_delegations(
+            &mut deps.querier,
+            &[("alpha", 0), ("beta", 0), ("gamma", 0)],
+        );
+        let msg = weighted_instantiate(&[("alpha", 1), ("beta", 3)]);
+        instantiate(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg).unwrap();
+        let info = mock_info("bob", &coins(1000, "ustake"));
+        execute(deps.as_mut(), mock_env(), info, ExecuteMsg::Bond {}).unwrap();
+        set_delegations(
+            &mut deps.querier,
+            &[("alpha", 250), ("beta", 750), ("gamma", 0)],
+        );
+
+        // only to a known validator outside our set
+        let change = |from: &str, to: &str| ExecuteMsg::ChangeValidator {
+            from: from.to_string(),
+            to: to.to_string(),
+        };
+        let info = mock_info("creator", &[]);
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            info.clone(),
+            change("alpha", "beta"),
+        )
+        .unwrap_err();
+        assert_eq!(
+            err,
+            ContractError::DuplicateValidator {
+                validator: "beta".into()
+            }
+        );
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            info.clone(),
+            change("alpha", "delta"),
+        )
+        .unwrap_err();
+        assert_eq!(
+            err,
+            ContractError::NotInValidatorSet {
+                validator: "delta".into()
+            }
+        );
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            info.clone(),
+            change("delta", "gamma"),
+        )
+        .unwrap_err();
+        assert_eq!(
+            err,
+            ContractError::UnknownValidator {
+                validator: "delta".into()
+            }
+        );
+
+        let res = execute(
+            deps.as_mut(),
+            mock_env(),
+            info.clone(),
+            change("beta", "gamma"),
+        )
+        .unwrap();
+        assert_eq!(
+            res.messages,
+            vec![SubMsg::new(StakingMsg::Redelegate {
+                src_validator: "beta".into(),
+                dst_validator: "gamma".into(),
+                amount: coin(750, "ustake"),
+            })]
+        );
+        let invest = query_investment(deps.as_ref(), mock_env()).unwrap();
+        assert_eq!(
+            invest.validators[1],
+            ValidatorWeight {
+                address: "gamma".into(),
+                weight: 3
+            }
+        );
+
+        // gamma's stake is still maturing, so it cannot be moved on yet
+        let mut matured = sample_delegation("gamma", coin(750, "ustake"));
+        matured.can_redelegate = coin(0, "ustake");
+        deps.querier.update_staking(
+            "ustake",
+            &[
+                sample_validator("alpha"),
+                sample_validator("beta"),
+                sample_validator("gamma"),
+            ],
+            &[sample_delegation("alpha", coin(250, "ustake")), matured],
+        );
+        let err = execute(deps.as_mut(), mock_env(), info, change("gamma", "beta")).unwrap_err();
+        assert_eq!(
+            err,
+            ContractError::RedelegationInProgress {
+                validator: "gamma".into()
+            }
+        );
+    }
+
+    #[test]
+    fn keeper_is_paid_for_reinvesting() {
+        let mut deps = mock_dependencies();
+        let mut delegation = sample_delegation(DEFAULT_VALIDATOR, coin(1000, "ustake"));
+        delegation.accumulated_rewards = coins(200, "ustake");
+        deps.querier.update_staking(
+            "ustake",
+            &[sample_validator(DEFAULT_VALIDATOR)],
+            &[delegation],
+        );
+
+        let mut msg = default_instantiate(2, 50);
+        msg.keeper_reward = Decimal::percent(5);
+        msg.reinvest_interval = Some(3600);
+        instantiate(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg).unwrap();
+
+        let invest = query_investment(deps.as_ref(), mock_env()).unwrap();
+        assert_eq!(invest.pending_rewards, coin(200, "ustake"));
+        assert_eq!(invest.last_reinvest, None);
+
+        // the keeper gets 5% of the harvest, paid after withdrawing and before rebonding
+        let env = mock_env();
+        let info = mock_info("keeper", &[]);
+        let res = execute(deps.as_mut(), env.clone(), info, ExecuteMsg::Reinvest {}).unwrap();
+        assert_eq!(3, res.messages.len());
+        assert_eq!(
+            res.messages[1],
+            SubMsg::new(BankMsg::Send {
+                to_address: "keeper".into(),
+                amount: coins(10, "ustake"),
+            })
+        );
+        let invest = query_investment(deps.as_ref(), mock_env()).unwrap();
+        assert_eq!(invest.last_reinvest, Some(env.block.time));
+
+        // not again within the interval
+        let info = mock_info("keeper", &[]);
+        let too_soon = later(&env, Duration::Time(1800));
+        let err = execute(
+            deps.as_mut(),
+            too_soon,
+            info.clone(),
+            ExecuteMsg::Reinvest {},
+        )
+        .unwrap_err();
+        assert_eq!(
+            err,
+            ContractError::ReinvestTooSoon {
+                next: HOUR.after(&env.block)
+            }
+        );
+        execute(
+            deps.as_mut(),
+            later(&env, HOUR),
+            info.clone(),
+            ExecuteMsg::Reinvest {},
+        )
+        .unwrap();
+
+        // a zero interval lifts the limit
+        let update = |interval| ExecuteMsg::UpdateConfig {
+            exit_tax: None,
+            min_withdrawal: None,
+            keeper_reward: None,
+            reinvest_interval: Some(interval),
+            buffer_ratio: None,
+            instant_unbond_fee: None,
+        };
+        let owner = mock_info("creator", &[]);
+        execute(deps.as_mut(), mock_env(), owner, update(0)).unwrap();
+        let invest = query_investment(deps.as_ref(), mock_env()).unwrap();
+        assert_eq!(invest.reinvest_interval, None);
+        execute(
+            deps.as_mut(),
+            later(&env, HOUR),
+            info,
+            ExecuteMsg::Reinvest {},
+        )
+        .unwrap();
+    }
+
+    #[test]
+    fn instant_unbond_from_buffer() {
+        let mut deps = mock_dependencies();
+        set_validator(&mut deps.querier);
+
+        let mut msg = default_instantiate(0, 50);
+        msg.buffer_ratio = Decimal::percent(10);
+        msg.instant_unbond_fee = Decimal::percent(1);
+        instantiate(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg).unwrap();
+
+        // 10% of the bond stays in the buffer, only the rest is delegated
+        let bob = String::from("bob");
+        let info = mock_info(&bob, &[coin(1000, "ustake")]);
+        let res = execute(deps.as_mut(), mock_env(), info, ExecuteMsg::Bond {}).unwrap();
+        assert_eq!(
+            res.messages,
+            vec![SubMsg::new(StakingMsg::Delegate {
+                validator: DEFAULT_VALIDATOR.into(),
+                amount: coin(900, "ustake"),
+            })]
+        );
+        set_delegation(&mut deps.querier, 900, "ustake");
+        assert_eq!(get_balance(deps.as_ref(), &bob), Uint128::new(1000));
+        let invest = query_investment(deps.as_ref(), mock_env()).unwrap();
+        assert_eq!(invest.staked_tokens, coin(900, "ustake"));
+        assert_eq!(invest.buffer, coin(100, "ustake"));
+        assert_eq!(invest.nominal_value, Decimal::one());
+
+        // paid out at once, the 1% fee stays in the pool
+        let info = mock_info(&bob, &[]);
+        let instant = ExecuteMsg::InstantUnbond {
+            amount: Uint128::new(100),
+        };
+        let res = execute(deps.as_mut(), mock_env(), info.clone(), instant.clone()).unwrap();
+        assert_eq!(
+            res.messages,
+            vec![SubMsg::new(BankMsg::Send {
+                to_address: bob.clone(),
+                amount: coins(99, "ustake"),
+            })]
+        );
+        assert!(get_claims(deps.as_ref(), &bob).is_empty());
+        assert_eq!(get_balance(deps.as_ref(), &bob), Uint128::new(900));
+        let invest = query_investment(deps.as_ref(), mock_env()).unwrap();
+        assert_eq!(invest.buffer, coin(1, "ustake"));
+        assert_eq!(invest.token_supply, Uint128::new(900));
+        assert_eq!(invest.nominal_value, Decimal::from_ratio(901u128, 900u128));
+
+        // the buffer is drained
+        let err = execute(deps.as_mut(), mock_env(), info, instant).unwrap_err();
+        assert_eq!(
+            err,
+            ContractError::BufferTooSmall {
+                available: Uint128::new(1)
+            }
+        );
+
+        // reinvesting tops the buffer up to 10% before bonding the rest
+        deps.querier
+            .update_balance(MOCK_CONTRACT_ADDR, coins(201, "ustake"));
+        let info = mock_info(MOCK_CONTRACT_ADDR, &[]);
+        let res = execute(
+            deps.as_mut(),
+            mock_env(),
+            info,
+            ExecuteMsg::_BondAllTokens {},
+        )
+        .unwrap();
+        assert_eq!(
+            res.messages,
+            vec![SubMsg::new(StakingMsg::Delegate {
+                validator: DEFAULT_VALIDATOR.into(),
+                amount: coin(91, "ustake"),
+            })]
+        );
+        let invest = query_investment(deps.as_ref(), mock_env()).unwrap();
+        assert_eq!(invest.buffer, coin(110, "ustake"));
+        assert_eq!(invest.staked_tokens, coin(991, "ustake"));
+    }
+
+    #[test]
+    fn claims_listing_and_unbonding_schedule() {
+        let mut deps = mock_dependencies();
+        set_validator(&mut deps.querier);
+        let msg = default_instantiate(0, 50);
+        instantiate(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg).unwrap();
+
+        let mut bonded = 0;
+        for (who, amount) in [("bob", 1000), ("alice", 500)] {
+            let info = mock_info(who, &[coin(amount, "ustake")]);
+            execute(deps.as_mut(), mock_env(), info, ExecuteMsg::Bond {}).unwrap();
+            bonded += amount;
+            set_delegation(&mut deps.querier, bonded, "ustake");
+        }
+
+        // bob and alice unbond at the same time, bob once more a day later
+        let env = mock_env();
+        let unbonds = [("bob", 200, &env), ("alice", 100, &env)];
+        let next_day = later(&env, DAY);
+        for (who, amount, env) in unbonds.iter().chain(&[("bob", 100, &next_day)]) {
+            let info = mock_info(who, &[]);
+            let unbond = ExecuteMsg::Unbond {
+                amount: Uint128::new(*amount),
+            };
+            execute(deps.as_mut(), (*env).clone(), info, unbond).unwrap();
+            bonded -= amount;
+            set_delegation(&mut deps.querier, bonded, "ustake");
+        }
+
+        // paging over all claimants
+        let page = query_all_claims(deps.as_ref(), None, Some(1)).unwrap();
+        assert_eq!(
+            page.claims,
+            vec![AddressClaims {
+                address: "alice".into(),
+                claims: get_claims(deps.as_ref(), "alice"),
+            }]
+        );
+        let page = query_all_claims(deps.as_ref(), Some("alice".into()), None).unwrap();
+        assert_eq!(page.claims.len(), 1);
+        assert_eq!(page.claims[0].address, "bob");
+        assert_eq!(page.claims[0].claims.len(), 2);
+
+        // summed up by release second, page by page
+        let first = env.block.time.seconds() + 3 * 86_400;
+        let second = next_day.block.time.seconds() + 3 * 86_400;
+        let entry = |release, amount| UnbondingEntry {
+            release_at: Expiration::AtTime(Timestamp::from_seconds(release)),
+            amount: Uint128::new(amount),
+        };
+        let schedule = query_unbonding_schedule(deps.as_ref(), None, Some(1)).unwrap();
+        assert_eq!(
+            schedule,
+            UnbondingScheduleResponse {
+                total: Uint128::new(400),
+                unbonding: vec![entry(first, 300)],
+            }
+        );
+        let schedule = query_unbonding_schedule(deps.as_ref(), Some(first), None).unwrap();
+        assert_eq!(schedule.unbonding, vec![entry(second, 100)]);
+
+        // claimants are listed until all their claims are paid out
+        deps.querier
+            .update_balance(MOCK_CONTRACT_ADDR, coins(1000, "ustake"));
+        let released = later(&env, DAY * 3);
+        execute(
+            deps.as_mut(),
+            released.clone(),
+            mock_info("alice", &[]),
+            ExecuteMsg::Claim {},
+        )
+        .unwrap();
+        execute(
+            deps.as_mut(),
+            released,
+            mock_info("bob", &[]),
+            ExecuteMsg::Claim {},
+        )
+        .unwrap();
+        let all = query_all_claims(deps.as_ref(), None, None).unwrap();
+        assert_eq!(all.claims.len(), 1);
+        assert_eq!(all.claims[0].address, "bob");
+        assert_eq!(all.claims[0].claims.len(), 1);
+
+        // and paid out claims leave the schedule
+        let schedule = query_unbonding_schedule(deps.as_ref(), None, None).unwrap();
+        assert_eq!(
+            schedule,
+            UnbondingScheduleResponse {
+                total: Uint128::new(100),
+                unbonding: vec![entry(second, 100)],
+            }
+        );
+    }
+
+    #[test]
+    fn exchange_rate_and_history() {
+        let mut deps = mock_dependencies();
+        set_validator(&mut deps.querier);
+        let mut msg = default_instantiate(0, 50);
+        msg.keeper_reward = Decimal::percent(10);
+        let env = mock_env();
+        instantiate(deps.as_mut(), env.clone(), mock_info("creator", &[]), msg).unwrap();
+
+        let info = mock_info("bob", &[coin(1000, "ustake")]);
+        execute(deps.as_mut(), env.clone(), info, ExecuteMsg::Bond {}).unwrap();
+
+        // pending rewards count, after the keeper took their share
+        let mut delegation = sample_delegation(DEFAULT_VALIDATOR, coin(1000, "ustake"));
+        delegation.accumulated_rewards = coins(200, "ustake");
+        deps.querier.update_staking(
+            "ustake",
+            &[sample_validator(DEFAULT_VALIDATOR)],
+            &[delegation],
+        );
+        let rate = query_exchange_rate(deps.as_ref(), env.clone()).unwrap();
+        assert_eq!(rate.rate, Decimal::percent(118));
+        assert_eq!(rate.rate_without_rewards, Decimal::one());
+
+        // 100 seconds later, 100 tokens get reinvested
+        let reinvest_env = later(&env, Duration::Time(100));
+        deps.querier
+            .update_balance(MOCK_CONTRACT_ADDR, coins(100, "ustake"));
+        let info = mock_info(MOCK_CONTRACT_ADDR, &[]);
+        execute(
+            deps.as_mut(),
+            reinvest_env.clone(),
+            info,
+            ExecuteMsg::_BondAllTokens {},
+        )
+        .unwrap();
+        set_delegation(&mut deps.querier, 1100, "ustake");
+
+        // recorded by the second
+        let start = Timestamp::from_seconds(env.block.time.seconds());
+        let history = query_rate_history(deps.as_ref(), None, None).unwrap();
+        assert_eq!(
+            history.rates,
+            vec![
+                RateRecord {
+                    time: start,
+                    rate: Decimal::one(),
+                },
+                RateRecord {
+                    time: start.plus_seconds(100),
+                    rate: Decimal::percent(110),
+                },
+            ]
+        );
+        let history =
+            query_rate_history(deps.as_ref(), Some(env.block.time.seconds()), None).unwrap();
+        assert_eq!(history.rates.len(), 1);
+
+        // 1.0 for 100 seconds and 1.1 for 100 seconds
+        let now = later(&env, Duration::Time(200));
+        let twap = query_time_weighted_rate(deps.as_ref(), now.clone(), 1000).unwrap();
+        assert_eq!(
+            twap,
+            TimeWeightedRateResponse {
+                rate: Decimal::percent(105),
+                start,
+                end: start.plus_seconds(200),
+            }
+        );
+        let twap = query_time_weighted_rate(deps.as_ref(), now.clone(), 150).unwrap();
+        assert_eq!(twap.rate, Decimal::from_ratio(50u128 + 110, 150u128));
+        let twap = query_time_weighted_rate(deps.as_ref(), now, 0).unwrap();
+        assert_eq!(twap.rate, Decimal::percent(110));
+    }
+
+    #[test]
+    fn unbond_through_send() {
+        let mut deps = mock_dependencies();
+        set_validator(&mut deps.querier);
+        let msg = default_instantiate(10, 50);
+        instantiate(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg).unwrap();
+
+        let info = mock_info("bob", &[coin(1000, "ustake")]);
+        execute(deps.as_mut(), mock_env(), info, ExecuteMsg::Bond {}).unwrap();
+        set_delegation(&mut deps.querier, 1000, "ustake");
+
+        // bob sends the tokens here, cw20-base calls back into this contract
+        let hook = to_binary(&ReceiveMsg::Unbond {
+            recipient: Some("alice".into()),
+        })
+        .unwrap();
+        let send = ExecuteMsg::Send {
+            contract: MOCK_CONTRACT_ADDR.into(),
+            amount: Uint128::new(500),
+            msg: hook.clone(),
+        };
+        let res = execute(deps.as_mut(), mock_env(), mock_info("bob", &[]), send).unwrap();
+        let receive = Cw20ReceiveMsg {
+            sender: "bob".into(),
+            amount: Uint128::new(500),
+            msg: hook,
+        };
+        assert_eq!(
+            res.messages,
+            vec![SubMsg::new(
+                receive.clone().into_cosmos_msg(MOCK_CONTRACT_ADDR).unwrap()
+            )]
+        );
+        assert_eq!(get_balance(deps.as_ref(), "bob"), Uint128::new(500));
+        assert_eq!(
+            get_balance(deps.as_ref(), MOCK_CONTRACT_ADDR),
+            Uint128::new(500)
+        );
+
+        // only our own token can call the hook
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("other-token", &[]),
+            ExecuteMsg::Receive(receive.clone()),
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::UnsupportedToken {});
+
+        // the tokens are burnt and alice gets the claim, minus the 10% exit tax
+        let info = mock_info(MOCK_CONTRACT_ADDR, &[]);
+        let res = execute(
+            deps.as_mut(),
+            mock_env(),
+            info,
+            ExecuteMsg::Receive(receive),
+        )
+        .unwrap();
+        assert_eq!(
+            res.messages,
+            vec![SubMsg::new(StakingMsg::Undelegate {
+                validator: DEFAULT_VALIDATOR.into(),
+                amount: coin(450, "ustake"),
+            })]
+        );
+        assert_eq!(
+            get_balance(deps.as_ref(), MOCK_CONTRACT_ADDR),
+            Uint128::zero()
+        );
+        assert_eq!(get_balance(deps.as_ref(), "creator"), Uint128::new(50));
+        assert!(get_claims(deps.as_ref(), "bob").is_empty());
+        assert_eq!(
+            get_claims(deps.as_ref(), "alice"),
+            vec![Claim::new(450, (DAY * 3).after(&mock_env().block))]
+        );
+        let invest = query_investment(deps.as_ref(), mock_env()).unwrap();
+        assert_eq!(invest.token_supply, Uint128::new(550));
+    }
 }
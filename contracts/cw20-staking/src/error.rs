@@ -1,4 +1,5 @@
 use cosmwasm_std::{StdError, Uint128};
+use cw_utils::Expiration;
 use thiserror::Error;
 
 #[derive(Error, Debug, PartialEq)]
@@ -9,9 +10,57 @@ pub enum ContractError {
     #[error("Unauthorized")]
     Unauthorized {},
 
+    #[error("Cannot migrate from {previous_contract}")]
+    CannotMigrate { previous_contract: String },
+
+    #[error("Cannot migrate from newer version {previous_version}")]
+    CannotMigrateVersion { previous_version: String },
+
+    #[error("Semver parsing error: {0}")]
+    SemVer(String),
+
+    #[error("Only the derivative token itself can be received")]
+    UnsupportedToken {},
+
     #[error("Validator '{validator}' not in current validator set")]
     NotInValidatorSet { validator: String },
 
+    #[error("At least one validator is required")]
+    NoValidators {},
+
+    #[error("Validator '{validator}' is listed more than once")]
+    DuplicateValidator { validator: String },
+
+    #[error("Validator '{validator}' must have a weight greater than zero")]
+    ZeroWeight { validator: String },
+
+    #[error("Validator '{validator}' is not one of ours")]
+    UnknownValidator { validator: String },
+
+    #[error("Stake redelegated to '{validator}' is still maturing, try again later")]
+    RedelegationInProgress { validator: String },
+
+    #[error("Exit tax cannot be more than 100%")]
+    InvalidExitTax {},
+
+    #[error("Keeper reward cannot be more than 100%")]
+    InvalidKeeperReward {},
+
+    #[error("Buffer ratio cannot be more than 100%")]
+    InvalidBufferRatio {},
+
+    #[error("Instant unbond fee cannot be more than 100%")]
+    InvalidInstantUnbondFee {},
+
+    #[error("Only {available} tokens are available for instant unbonding")]
+    BufferTooSmall { available: Uint128 },
+
+    #[error("Cannot reinvest again before {next}")]
+    ReinvestTooSoon { next: Expiration },
+
+    #[error("No ownership transfer has been proposed")]
+    NoPendingOwner {},
+
     #[error("Different denominations in bonds: '{denom1}' vs. '{denom2}'")]
     DifferentBondDenom { denom1: String, denom2: String },
 
@@ -52,6 +101,12 @@ pub enum ContractError {
     DuplicateInitialBalanceAddresses {},
 }
 
+impl From<semver::Error> for ContractError {
+    fn from(err: semver::Error) -> Self {
+        Self::SemVer(err.to_string())
+    }
+}
+
 impl From<cw20_base::ContractError> for ContractError {
     fn from(err: cw20_base::ContractError) -> Self {
         match err {
@@ -0,0 +1,163 @@
+#![cfg(test)]
+
+use cosmwasm_std::testing::mock_env;
+use cosmwasm_std::{coin, coins, Addr, Decimal, Empty, Uint128, Validator};
+use cw20::BalanceResponse;
+use cw_multi_test::{App, Contract, ContractWrapper, Executor, StakingSudo, SudoMsg};
+use cw_utils::DAY;
+
+use crate::msg::{
+    ClaimsResponse, ExecuteMsg, InstantiateMsg, InvestmentResponse, QueryMsg, ValidatorWeight,
+};
+
+const VALIDATOR: &str = "validator";
+const DENOM: &str = "TOKEN";
+
+pub fn contract_staking() -> Box<dyn Contract<Empty>> {
+    let contract = ContractWrapper::new(
+        crate::contract::execute,
+        crate::contract::instantiate,
+        crate::contract::query,
+    );
+    Box::new(contract)
+}
+
+fn balance(app: &App, contract: &Addr, address: &Addr) -> Uint128 {
+    let res: BalanceResponse = app
+        .wrap()
+        .query_wasm_smart(
+            contract,
+            &QueryMsg::Balance {
+                address: address.to_string(),
+            },
+        )
+        .unwrap();
+    res.balance
+}
+
+fn investment(app: &App, contract: &Addr) -> InvestmentResponse {
+    app.wrap()
+        .query_wasm_smart(contract, &QueryMsg::Investment {})
+        .unwrap()
+}
+
+#[test]
+// slashing lowers the value of the derivative instead of blocking the contract
+fn slashing_is_absorbed() {
+    let owner = Addr::unchecked("owner");
+    let bob = Addr::unchecked("bob");
+    let alice = Addr::unchecked("alice");
+
+    let mut app = App::new(|router, api, storage| {
+        router
+            .bank
+            .init_balance(storage, &bob, coins(1000, DENOM))
+            .unwrap();
+        router
+            .bank
+            .init_balance(storage, &alice, coins(900, DENOM))
+            .unwrap();
+        let validator = Validator {
+            address: VALIDATOR.to_string(),
+            commission: Decimal::percent(10),
+            max_commission: Decimal::percent(20),
+            max_change_rate: Decimal::percent(1),
+        };
+        router
+            .staking
+            .add_validator(api, storage, &mock_env().block, validator)
+            .unwrap();
+    });
+
+    let staking_id = app.store_code(contract_staking());
+    let msg = InstantiateMsg {
+        name: "Cool Derivative".to_string(),
+        symbol: "DRV".to_string(),
+        decimals: 6,
+        validators: vec![ValidatorWeight {
+            address: VALIDATOR.to_string(),
+            weight: 1,
+        }],
+        unbonding_period: DAY * 3,
+        exit_tax: Decimal::percent(2),
+        min_withdrawal: Uint128::new(50),
+        keeper_reward: Decimal::zero(),
+        reinvest_interval: None,
+        buffer_ratio: Decimal::zero(),
+        instant_unbond_fee: Decimal::zero(),
+    };
+    let staking = app
+        .instantiate_contract(staking_id, owner.clone(), &msg, &[], "Staking", None)
+        .unwrap();
+
+    // bob bonds at a 1.0 ratio
+    app.execute_contract(
+        bob.clone(),
+        staking.clone(),
+        &ExecuteMsg::Bond {},
+        &coins(1000, DENOM),
+    )
+    .unwrap();
+    assert_eq!(balance(&app, &staking, &bob), Uint128::new(1000));
+
+    // the validator loses 10% of its stake
+    app.sudo(SudoMsg::Staking(StakingSudo::Slash {
+        validator: VALIDATOR.to_string(),
+        percentage: Decimal::percent(10),
+    }))
+    .unwrap();
+
+    // alice bonds after the slash, this is noticed and she gets the lower price
+    let res = app
+        .execute_contract(
+            alice.clone(),
+            staking.clone(),
+            &ExecuteMsg::Bond {},
+            &coins(900, DENOM),
+        )
+        .unwrap();
+    let slashed = res
+        .events
+        .iter()
+        .find(|e| e.ty == "wasm-slashed")
+        .expect("slashing event");
+    assert!(slashed
+        .attributes
+        .iter()
+        .any(|a| a.key == "slashed" && a.value == "100"));
+    assert_eq!(balance(&app, &staking, &alice), Uint128::new(1000));
+
+    let invest = investment(&app, &staking);
+    assert_eq!(invest.token_supply, Uint128::new(2000));
+    assert_eq!(invest.staked_tokens, coin(1800, DENOM));
+    assert_eq!(invest.nominal_value, Decimal::percent(90));
+
+    // bob unbonds at the lower value: 10 DRV tax, 490 DRV worth 441 TOKEN
+    let res = app
+        .execute_contract(
+            bob.clone(),
+            staking.clone(),
+            &ExecuteMsg::Unbond {
+                amount: Uint128::new(500),
+            },
+            &[],
+        )
+        .unwrap();
+    assert!(!res.events.iter().any(|e| e.ty == "wasm-slashed"));
+    let claims: ClaimsResponse = app
+        .wrap()
+        .query_wasm_smart(
+            &staking,
+            &QueryMsg::Claims {
+                address: bob.to_string(),
+            },
+        )
+        .unwrap();
+    assert_eq!(claims.claims[0].amount, Uint128::new(441));
+    assert_eq!(balance(&app, &staking, &owner), Uint128::new(10));
+
+    let invest = investment(&app, &staking);
+    assert_eq!(invest.staked_tokens, coin(1359, DENOM));
+    assert_eq!(invest.token_supply, Uint128::new(1510));
+    assert_eq!(invest.nominal_value, Decimal::percent(90));
+}
@@ -1,5 +1,7 @@
 pub mod contract;
 mod error;
+mod integration_test;
+mod migrations;
 pub mod msg;
 pub mod state;
 
@@ -0,0 +1,90 @@
+// Migration logic for contracts with version: 0.14.2
+pub mod v0_14_2 {
+    use cosmwasm_schema::cw_serde;
+    use cosmwasm_std::{Addr, Decimal, DepsMut, Empty, Order, StdResult, Storage, Uint128};
+    use cw_controllers::Claim;
+    use cw_storage_plus::{Bound, Item, Map};
+    use cw_utils::Duration;
+
+    use crate::msg::ValidatorWeight;
+    use crate::state::{add_unbonding, InvestmentInfo, CLAIMANTS, INVESTMENT};
+    use crate::ContractError;
+
+    /// InvestmentInfo as stored when all tokens were bonded to a single validator
+    #[cw_serde]
+    struct LegacyInvestmentInfo {
+        owner: Addr,
+        bond_denom: String,
+        unbonding_period: Duration,
+        exit_tax: Decimal,
+        validator: String,
+        min_withdrawal: Uint128,
+    }
+
+    const LEGACY_INVESTMENT: Item<LegacyInvestmentInfo> = Item::new("invest");
+    /// Claims as cw-controllers 0.16 stores them in CLAIMS, only read to index the claims
+    const LEGACY_CLAIMS: Map<&Addr, Vec<Claim>> = Map::new("claims");
+    /// While claims are being indexed, the last address indexed so far (None before the first)
+    const CLAIMS_CURSOR: Item<Option<Addr>> = Item::new("migrate_claims_cursor");
+
+    pub fn migrate_investment(deps: DepsMut) -> Result<(), ContractError> {
+        // the single validator gets all the weight, every feature added since is turned off
+        let legacy = LEGACY_INVESTMENT.load(deps.storage)?;
+        let invest = InvestmentInfo {
+            owner: legacy.owner,
+            bond_denom: legacy.bond_denom,
+            unbonding_period: legacy.unbonding_period,
+            exit_tax: legacy.exit_tax,
+            validators: vec![ValidatorWeight {
+                address: legacy.validator,
+                weight: 1,
+            }],
+            min_withdrawal: legacy.min_withdrawal,
+            keeper_reward: Decimal::zero(),
+            reinvest_interval: None,
+            buffer_ratio: Decimal::zero(),
+            instant_unbond_fee: Decimal::zero(),
+        };
+        INVESTMENT.save(deps.storage, &invest)?;
+
+        // claims were not indexed yet either, that is done by `index_claims`
+        CLAIMS_CURSOR.save(deps.storage, &None)?;
+        Ok(())
+    }
+
+    /// Whether there are claims left to index
+    pub fn indexing_claims(storage: &dyn Storage) -> StdResult<bool> {
+        Ok(CLAIMS_CURSOR.may_load(storage)?.is_some())
+    }
+
+    /// Indexes the claims of up to `limit` more addresses. Returns true once all are indexed
+    pub fn index_claims(deps: DepsMut, limit: u32) -> Result<bool, ContractError> {
+        let cursor = CLAIMS_CURSOR.load(deps.storage)?;
+        let batch = LEGACY_CLAIMS
+            .range(
+                deps.storage,
+                cursor.as_ref().map(Bound::exclusive),
+                None,
+                Order::Ascending,
+            )
+            .take(limit as usize)
+            .collect::<StdResult<Vec<_>>>()?;
+        for (address, claims) in &batch {
+            if !claims.is_empty() {
+                CLAIMANTS.save(deps.storage, address, &Empty {})?;
+            }
+            for claim in claims {
+                add_unbonding(deps.storage, &claim.release_at, claim.amount)?;
+            }
+        }
+
+        if batch.len() < limit as usize {
+            CLAIMS_CURSOR.remove(deps.storage);
+            Ok(true)
+        } else {
+            let last = batch.last().map(|(address, _)| address.clone());
+            CLAIMS_CURSOR.save(deps.storage, &last)?;
+            Ok(false)
+        }
+    }
+}
@@ -1,9 +1,9 @@
 use cosmwasm_schema::{cw_serde, QueryResponses};
 
-use cosmwasm_std::{Binary, Coin, Decimal, Uint128};
+use cosmwasm_std::{Binary, Coin, Decimal, Timestamp, Uint128};
 use cw20::Expiration;
-use cw20::{AllowanceResponse, BalanceResponse, TokenInfoResponse};
-pub use cw_controllers::ClaimsResponse;
+use cw20::{AllowanceResponse, BalanceResponse, Cw20ReceiveMsg, TokenInfoResponse};
+pub use cw_controllers::{Claim, ClaimsResponse};
 use cw_utils::Duration;
 
 #[cw_serde]
@@ -15,8 +15,8 @@ pub struct InstantiateMsg {
     /// decimal places of the derivative token (for UI)
     pub decimals: u8,
 
-    /// This is the validator that all tokens will be bonded to
-    pub validator: String,
+    /// These are the validators that tokens will be bonded to, split by weight
+    pub validators: Vec<ValidatorWeight>,
     /// This is the unbonding period of the native staking module
     /// We need this to only allow claims to be redeemed after the money has arrived
     pub unbonding_period: Duration,
@@ -26,6 +26,24 @@ pub struct InstantiateMsg {
     /// This is the minimum amount we will pull out to reinvest, as well as a minimum
     /// that can be unbonded (to avoid needless staking tx)
     pub min_withdrawal: Uint128,
+
+    /// This share of the harvested rewards is paid to whoever calls `Reinvest`
+    pub keeper_reward: Decimal,
+    /// If set, `Reinvest` can only be called once per this many seconds. Zero means no limit
+    pub reinvest_interval: Option<u64>,
+
+    /// This share of all staked value is kept undelegated, to pay out `InstantUnbond`
+    pub buffer_ratio: Decimal,
+    /// This share of the payout is kept back by `InstantUnbond`, for all remaining holders
+    pub instant_unbond_fee: Decimal,
+}
+
+#[cw_serde]
+pub struct ValidatorWeight {
+    /// Operator address of the validator
+    pub address: String,
+    /// Share of the stake this validator gets, relative to the sum of all weights
+    pub weight: u64,
 }
 
 #[cw_serde]
@@ -35,13 +53,43 @@ pub enum ExecuteMsg {
     /// Unbond will "burn" the given amount of derivative tokens and send the unbonded
     /// staking tokens to the message sender (after exit tax is deducted)
     Unbond { amount: Uint128 },
+    /// InstantUnbond will "burn" the given amount of derivative tokens and pay out the staking
+    /// tokens at once from the liquidity buffer, after exit tax and the instant unbond fee
+    InstantUnbond { amount: Uint128 },
+    /// Receive is called when derivative tokens are sent to this contract with a cw20 `Send`.
+    /// The embedded msg must be a `ReceiveMsg`
+    Receive(Cw20ReceiveMsg),
     /// Claim is used to claim your native tokens that you previously "unbonded"
     /// after the chain-defined waiting period (eg. 3 weeks)
     Claim {},
     /// Reinvest will check for all accumulated rewards, withdraw them, and
-    /// re-bond them to the same validator. Anyone can call this, which updates
-    /// the value of the token (how much under custody).
+    /// re-bond them to the validators by weight. Anyone can call this, which updates
+    /// the value of the token (how much under custody). The caller gets the keeper reward.
     Reinvest {},
+    /// Rebalance can only be called by the owner. It redelegates stake between the validators
+    /// to restore their target weights, eg. after unbonding or slashing changed the split.
+    Rebalance {},
+    /// UpdateConfig can only be called by the owner. Changes the exit tax, minimum
+    /// withdrawal, keeper reward, reinvest interval, buffer ratio and instant unbond fee,
+    /// fields left as None keep their current value. The reinvest interval is in seconds,
+    /// zero removes the limit
+    UpdateConfig {
+        exit_tax: Option<Decimal>,
+        min_withdrawal: Option<Uint128>,
+        keeper_reward: Option<Decimal>,
+        reinvest_interval: Option<u64>,
+        buffer_ratio: Option<Decimal>,
+        instant_unbond_fee: Option<Decimal>,
+    },
+    /// ProposeOwner can only be called by the owner. The proposed owner takes over once they
+    /// call `AcceptOwnership`. Proposing again replaces the previous proposal
+    ProposeOwner { owner: String },
+    /// AcceptOwnership can only be called by the proposed owner, and makes them the owner
+    AcceptOwnership {},
+    /// ChangeValidator can only be called by the owner. It replaces `from` with `to` in the
+    /// validator set, keeping its weight, and redelegates everything bonded to `from` to `to`.
+    /// Fails while stake redelegated to `from` is still maturing
+    ChangeValidator { from: String, to: String },
     /// _BondAllTokens can only be called by the contract itself, after all rewards have been
     /// withdrawn. This is an example of using "callbacks" in message flows.
     /// This can only be invoked by the contract itself as a return from Reinvest
@@ -93,15 +141,48 @@ pub enum ExecuteMsg {
     BurnFrom { owner: String, amount: Uint128 },
 }
 
+#[cw_serde]
+pub enum ReceiveMsg {
+    /// Unbond burns the sent tokens just like `ExecuteMsg::Unbond`, but the claim goes to
+    /// `recipient` (or the sender if None)
+    Unbond { recipient: Option<String> },
+}
+
 #[cw_serde]
 #[derive(QueryResponses)]
 pub enum QueryMsg {
     /// Claims shows the number of tokens this address can access when they are done unbonding
     #[returns(ClaimsResponse)]
     Claims { address: String },
+    /// Lists the claims of all addresses, ordered by address
+    #[returns(AllClaimsResponse)]
+    AllClaims {
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+    /// Sums up all pending claims by their release, in release order. `start_after` is a
+    /// release height or time in seconds, depending on the unbonding period
+    #[returns(UnbondingScheduleResponse)]
+    UnbondingSchedule {
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    },
     /// Investment shows metadata on the staking info of the contract
     #[returns(InvestmentResponse)]
     Investment {},
+    /// How many native tokens one derivative token is worth, including pending rewards
+    #[returns(ExchangeRateResponse)]
+    ExchangeRate {},
+    /// The average exchange rate (without pending rewards) over the last `window` seconds,
+    /// or since the first recorded rate if that is more recent
+    #[returns(TimeWeightedRateResponse)]
+    TimeWeightedRate { window: u64 },
+    /// Lists the recorded exchange rates, ordered by time in seconds
+    #[returns(RateHistoryResponse)]
+    RateHistory {
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    },
 
     /// Implements CW20. Returns the current balance of the given address, 0 if unset.
     #[returns(BalanceResponse)]
@@ -115,20 +196,96 @@ pub enum QueryMsg {
     Allowance { owner: String, spender: String },
 }
 
+#[cw_serde]
+pub struct AddressClaims {
+    pub address: String,
+    pub claims: Vec<Claim>,
+}
+
+#[cw_serde]
+pub struct AllClaimsResponse {
+    pub claims: Vec<AddressClaims>,
+}
+
+#[cw_serde]
+pub struct UnbondingEntry {
+    pub release_at: Expiration,
+    pub amount: Uint128,
+}
+
+#[cw_serde]
+pub struct UnbondingScheduleResponse {
+    /// all tokens reserved for claims, matured or not
+    pub total: Uint128,
+    /// pending claims summed up by release, in release order. Entries that are expired
+    /// already have matured, but are not claimed yet
+    pub unbonding: Vec<UnbondingEntry>,
+}
+
+#[cw_serde]
+pub struct ExchangeRateResponse {
+    /// (staked_tokens + buffer + pending rewards after the keeper reward) / token_supply
+    pub rate: Decimal,
+    /// The same without pending rewards, as used to bond and unbond
+    pub rate_without_rewards: Decimal,
+}
+
+#[cw_serde]
+pub struct TimeWeightedRateResponse {
+    pub rate: Decimal,
+    /// The average is taken from start until end
+    pub start: Timestamp,
+    pub end: Timestamp,
+}
+
+#[cw_serde]
+pub struct RateRecord {
+    pub time: Timestamp,
+    pub rate: Decimal,
+}
+
+#[cw_serde]
+pub struct RateHistoryResponse {
+    pub rates: Vec<RateRecord>,
+}
+
+#[cw_serde]
+pub struct MigrateMsg {
+    /// When migrating from 0.14.2 or older, the claims of this many addresses are indexed per
+    /// migrate call (default 100). Migrate again until the `claims_indexed` attribute is true
+    pub limit: Option<u32>,
+}
+
 #[cw_serde]
 pub struct InvestmentResponse {
     pub token_supply: Uint128,
     pub staked_tokens: Coin,
-    // ratio of staked_tokens / token_supply (or how many native tokens that one derivative token is nominally worth)
+    // native tokens kept undelegated in the liquidity buffer
+    pub buffer: Coin,
+    // ratio of (staked_tokens + buffer) / token_supply (or how many native tokens that one derivative token is nominally worth)
     pub nominal_value: Decimal,
 
     /// owner created the contract and takes a cut
     pub owner: String,
+    /// this account was proposed as the new owner, but has not accepted yet
+    pub pending_owner: Option<String>,
     /// this is how much the owner takes as a cut when someone unbonds
     pub exit_tax: Decimal,
-    /// All tokens are bonded to this validator
-    pub validator: String,
+    /// Tokens are bonded to these validators, split by weight
+    pub validators: Vec<ValidatorWeight>,
     /// This is the minimum amount we will pull out to reinvest, as well as a minimum
     /// that can be unbonded (to avoid needless staking tx)
     pub min_withdrawal: Uint128,
+    /// This share of the harvested rewards is paid to whoever calls `Reinvest`
+    pub keeper_reward: Decimal,
+    /// If set, `Reinvest` can only be called once per this many seconds
+    pub reinvest_interval: Option<u64>,
+    /// When `Reinvest` was last called, if ever
+    pub last_reinvest: Option<Timestamp>,
+    /// Rewards accumulated on all delegations that the next `Reinvest` will harvest
+    pub pending_rewards: Coin,
+    /// This share of all staked value is kept undelegated, to pay out `InstantUnbond`
+    pub buffer_ratio: Decimal,
+    /// This share of the payout is kept back by `InstantUnbond`, for all remaining holders
+    pub instant_unbond_fee: Decimal,
 }
@@ -1,12 +1,58 @@
 use cosmwasm_schema::cw_serde;
-use cosmwasm_std::{Addr, Decimal, Uint128};
+use cosmwasm_std::{Addr, Decimal, Empty, StdResult, Storage, Timestamp, Uint128, Uint256};
 use cw_controllers::Claims;
-use cw_storage_plus::Item;
-use cw_utils::Duration;
+use cw_storage_plus::{Item, Map};
+use cw_utils::{Duration, Expiration};
+
+use crate::msg::ValidatorWeight;
 
 pub const CLAIMS: Claims = Claims::new("claims");
+/// Every address with claims in CLAIMS, to list all claims. Added when a claim is created,
+/// removed once all claims of the address are paid out
+pub const CLAIMANTS: Map<&Addr, Empty> = Map::new("claimants");
+/// All claims in CLAIMS summed up by release, keyed by the release height or time in seconds
+/// (depending on the unbonding period)
+pub const UNBONDING: Map<u64, Uint128> = Map::new("unbonding");
+
+fn release_key(release_at: &Expiration) -> u64 {
+    match release_at {
+        Expiration::AtHeight(height) => *height,
+        Expiration::AtTime(time) => time.seconds(),
+        Expiration::Never {} => u64::MAX,
+    }
+}
 
-/// Investment info is fixed at instantiation, and is used to control the function of the contract
+/// Adds a new claim to UNBONDING
+pub fn add_unbonding(
+    storage: &mut dyn Storage,
+    release_at: &Expiration,
+    amount: Uint128,
+) -> StdResult<()> {
+    UNBONDING.update(storage, release_key(release_at), |total| -> StdResult<_> {
+        Ok(total.unwrap_or_default() + amount)
+    })?;
+    Ok(())
+}
+
+/// Removes a paid out claim from UNBONDING
+pub fn remove_unbonding(
+    storage: &mut dyn Storage,
+    release_at: &Expiration,
+    amount: Uint128,
+) -> StdResult<()> {
+    let key = release_key(release_at);
+    let total = UNBONDING.may_load(storage, key)?.unwrap_or_default();
+    let total = total.checked_sub(amount)?;
+    if total.is_zero() {
+        UNBONDING.remove(storage, key);
+    } else {
+        UNBONDING.save(storage, key, &total)?;
+    }
+    Ok(())
+}
+
+/// Investment info is set at instantiation, and is used to control the function of the contract.
+/// The owner can change it later on
 #[cw_serde]
 pub struct InvestmentInfo {
     /// Owner created the contract and takes a cut
@@ -18,12 +64,20 @@ pub struct InvestmentInfo {
     pub unbonding_period: Duration,
     /// This is how much the owner takes as a cut when someone unbonds
     pub exit_tax: Decimal,
-    /// All tokens are bonded to this validator
+    /// Tokens are bonded to these validators, split by weight
     /// FIXME: address validation doesn't work for validator addresses
-    pub validator: String,
+    pub validators: Vec<ValidatorWeight>,
     /// This is the minimum amount we will pull out to reinvest, as well as a minimum
     /// that can be unbonded (to avoid needless staking tx)
     pub min_withdrawal: Uint128,
+    /// This share of the harvested rewards is paid to whoever calls reinvest
+    pub keeper_reward: Decimal,
+    /// If set, reinvest can only be called once per this many seconds
+    pub reinvest_interval: Option<u64>,
+    /// This share of all staked value is kept undelegated, to pay out instant unbonds
+    pub buffer_ratio: Decimal,
+    /// This share of the payout is kept back by instant unbonds, for all remaining holders
+    pub instant_unbond_fee: Decimal,
 }
 
 /// Supply is dynamic and tracks the current supply of staked and ERC20 tokens.
@@ -32,11 +86,31 @@ pub struct InvestmentInfo {
 pub struct Supply {
     /// issued is how many derivative tokens this contract has issued
     pub issued: Uint128,
-    /// bonded is how many native tokens exist bonded to the validator
+    /// bonded is how many native tokens exist bonded to the validators
     pub bonded: Uint128,
     /// claims is how many tokens need to be reserved paying back those who unbonded
     pub claims: Uint128,
+    /// buffer is how many native tokens are kept undelegated for instant unbonds
+    #[serde(default)]
+    pub buffer: Uint128,
 }
 
 pub const INVESTMENT: Item<InvestmentInfo> = Item::new("invest");
+/// The owner proposed by the current owner, until they accept
+pub const PENDING_OWNER: Item<Addr> = Item::new("pending_owner");
 pub const TOTAL_SUPPLY: Item<Supply> = Item::new("total_supply");
+/// The time of the last reinvest, to enforce the reinvest interval
+pub const LAST_REINVEST: Item<Timestamp> = Item::new("last_reinvest");
+
+/// The exchange rate as recorded by bond, unbond and reinvest
+#[cw_serde]
+pub struct RateObservation {
+    pub rate: Decimal,
+    /// The rate (in atomics) summed up over every second from the first observation until
+    /// this one, so the time-weighted average between two points in time is the difference
+    /// of their cumulative values divided by the seconds in between
+    pub cumulative: Uint256,
+}
+
+/// Exchange rate history, by block time in seconds
+pub const RATE_HISTORY: Map<u64, RateObservation> = Map::new("rate_history");
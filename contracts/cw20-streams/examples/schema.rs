@@ -1,10 +1,11 @@
 use cosmwasm_schema::write_api;
-use cw20_streams::msg::{ExecuteMsg, InstantiateMsg, QueryMsg};
+use cw20_streams::msg::{ExecuteMsg, InstantiateMsg, MigrateMsg, QueryMsg};
 
 fn main() {
     write_api! {
         instantiate: InstantiateMsg,
         query: QueryMsg,
         execute: ExecuteMsg,
+        migrate: MigrateMsg,
     }
 }
@@ -1,22 +1,30 @@
 use crate::error::ContractError;
+use crate::migrations::v0_14_2;
 use crate::msg::{
-    ConfigResponse, ExecuteMsg, InstantiateMsg, ListStreamsResponse, QueryMsg, ReceiveMsg,
-    StreamParams, StreamResponse,
+    ConfigResponse, Cw721ReceiveMsg, ExecuteMsg, InstantiateMsg, ListStreamsResponse, MigrateMsg,
+    NumTokensResponse, OwnerOfResponse, QueryMsg, ReceiveMsg, Schedule, SchedulePoint,
+    StreamParams, StreamReceiveMsg, StreamResponse, TokensResponse,
 };
-use crate::state::{save_stream, Config, Stream, CONFIG, STREAMS, STREAM_SEQ};
+use crate::state::{save_stream, streams, Config, Stream, CONFIG, STREAM_COUNT, STREAM_SEQ};
 #[cfg(not(feature = "library"))]
 use cosmwasm_std::entry_point;
 use cosmwasm_std::{
-    from_binary, to_binary, Binary, Deps, DepsMut, Env, MessageInfo, Order, Response, StdResult,
-    Uint128,
+    coins, from_binary, to_binary, Addr, BankMsg, Binary, CosmosMsg, Decimal, Deps, DepsMut, Empty,
+    Env, MessageInfo, Order, Response, StdError, StdResult, Uint128,
 };
-use cw2::set_contract_version;
-use cw20::{Cw20Contract, Cw20ExecuteMsg, Cw20ReceiveMsg};
+use cw2::{get_contract_version, set_contract_version};
+use cw20::{Cw20Contract, Cw20ExecuteMsg, Cw20ReceiveMsg, Denom};
 use cw_storage_plus::Bound;
+use semver::Version;
 
 const CONTRACT_NAME: &str = "crates.io:cw20-streams";
 const CONTRACT_VERSION: &str = env!("CARGO_PKG_VERSION");
 
+// settings for pagination
+const MAX_LIMIT: u32 = 30;
+const DEFAULT_LIMIT: u32 = 10;
+const DEFAULT_MIGRATE_LIMIT: u32 = 100;
+
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn instantiate(
     deps: DepsMut,
@@ -33,16 +41,15 @@ pub fn instantiate(
 
     let config = Config {
         owner: owner.clone(),
-        cw20_addr: deps.api.addr_validate(&msg.cw20_addr)?,
     };
     CONFIG.save(deps.storage, &config)?;
 
     STREAM_SEQ.save(deps.storage, &0u64)?;
+    STREAM_COUNT.save(deps.storage, &0u64)?;
 
     Ok(Response::new()
         .add_attribute("method", "instantiate")
-        .add_attribute("owner", owner)
-        .add_attribute("cw20_addr", msg.cw20_addr))
+        .add_attribute("owner", owner))
 }
 
 #[cfg_attr(not(feature = "library"), entry_point)]
@@ -54,27 +61,129 @@ pub fn execute(
 ) -> Result<Response, ContractError> {
     match msg {
         ExecuteMsg::Receive(msg) => execute_receive(env, deps, info, msg),
-        ExecuteMsg::Withdraw { id } => execute_withdraw(env, deps, info, id),
+        ExecuteMsg::CreateStream {
+            recipient,
+            start_time,
+            end_time,
+            schedule,
+        } => execute_create_native_stream(
+            env,
+            deps,
+            info,
+            recipient,
+            start_time,
+            end_time,
+            schedule.unwrap_or_default(),
+        ),
+        ExecuteMsg::Withdraw { id, amount, to } => {
+            execute_withdraw(env, deps, info, id, amount, to)
+        }
+        ExecuteMsg::WithdrawAndSend {
+            id,
+            amount,
+            contract,
+            msg,
+        } => execute_withdraw_and_send(env, deps, info, id, amount, contract, msg),
+        ExecuteMsg::CancelStream { id } => execute_cancel_stream(env, deps, info, id),
+        ExecuteMsg::PauseStream { id } => execute_pause_stream(env, deps, info, id),
+        ExecuteMsg::ResumeStream { id } => execute_resume_stream(env, deps, info, id),
+        ExecuteMsg::TransferStream { id, new_recipient } => {
+            execute_transfer_stream(deps, info, id, new_recipient)
+        }
+        ExecuteMsg::TransferNft {
+            recipient,
+            token_id,
+        } => execute_transfer_stream(deps, info, parse_token_id(&token_id)?, recipient),
+        ExecuteMsg::SendNft {
+            contract,
+            token_id,
+            msg,
+        } => {
+            let id = parse_token_id(&token_id)?;
+            let sender = info.sender.to_string();
+            let res = execute_transfer_stream(deps, info, id, contract.clone())?;
+            let receive = Cw721ReceiveMsg {
+                sender,
+                token_id,
+                msg,
+            };
+            Ok(res.add_message(receive.into_cosmos_msg::<_, Empty>(contract)?))
+        }
+    }
+}
+
+/// payout sends amount of the stream's denom to recipient
+fn payout(denom: &Denom, recipient: &Addr, amount: Uint128) -> StdResult<CosmosMsg> {
+    match denom {
+        Denom::Native(denom) => Ok(BankMsg::Send {
+            to_address: recipient.into(),
+            amount: coins(amount.u128(), denom),
+        }
+        .into()),
+        Denom::Cw20(addr) => Cw20Contract(addr.clone()).call(Cw20ExecuteMsg::Transfer {
+            recipient: recipient.into(),
+            amount,
+        }),
+    }
+}
+
+fn validate_schedule(
+    schedule: &Schedule,
+    start_time: u64,
+    end_time: u64,
+) -> Result<(), ContractError> {
+    let invalid = |reason: &str| ContractError::InvalidSchedule {
+        reason: reason.to_string(),
+    };
+    match schedule {
+        Schedule::Linear => {}
+        Schedule::Cliff { cliff_time } => {
+            if *cliff_time < start_time || *cliff_time > end_time {
+                return Err(invalid("cliff must be between start and end time"));
+            }
+        }
+        Schedule::Stepwise { interval } => {
+            if *interval == 0 {
+                return Err(invalid("interval must be greater than zero"));
+            }
+        }
+        Schedule::Piecewise { points } => {
+            let mut last = SchedulePoint {
+                time: start_time,
+                vested: Decimal::zero(),
+            };
+            for point in points {
+                if point.time <= last.time || point.vested < last.vested {
+                    return Err(invalid("points must be in order and never vest less"));
+                }
+                last = point.clone();
+            }
+            if last.time != end_time || last.vested != Decimal::one() {
+                return Err(invalid("last point must vest everything at end time"));
+            }
+        }
     }
+    Ok(())
 }
 
 pub fn execute_create_stream(
     env: Env,
     deps: DepsMut,
-    config: Config,
     params: StreamParams,
 ) -> Result<Response, ContractError> {
     let StreamParams {
         owner,
         recipient,
+        denom,
         amount,
         start_time,
         end_time,
+        schedule,
     } = params;
     let owner = deps.api.addr_validate(&owner)?;
     let recipient = deps.api.addr_validate(&recipient)?;
 
-    if start_time > end_time {
+    if start_time >= end_time {
         return Err(ContractError::InvalidStartTime {});
     }
 
@@ -83,93 +192,155 @@ pub fn execute_create_stream(
         return Err(ContractError::InvalidStartTime {});
     }
 
-    let duration: Uint128 = (end_time - start_time).into();
-
-    if amount < duration {
-        return Err(ContractError::AmountLessThanDuration {});
-    }
-
-    // Duration must divide evenly into amount, so refund remainder
-    let refund: u128 = amount
-        .u128()
-        .checked_rem(duration.u128())
-        .ok_or(ContractError::Overflow {})?;
-
-    let amount = amount - Uint128::new(refund);
-
-    let rate_per_second = amount / duration;
+    validate_schedule(&schedule, start_time, end_time)?;
 
     let stream = Stream {
         owner: owner.clone(),
         recipient: recipient.clone(),
+        denom,
         amount,
         claimed_amount: Uint128::zero(),
         start_time,
         end_time,
-        rate_per_second,
+        schedule,
+        cancelled: false,
+        paused_at: None,
+        paused_duration: 0,
     };
     let id = save_stream(deps, &stream)?;
 
-    let mut response = Response::new()
+    let response = Response::new()
         .add_attribute("method", "create_stream")
         .add_attribute("stream_id", id.to_string())
-        .add_attribute("owner", owner.clone())
+        .add_attribute("owner", owner)
         .add_attribute("recipient", recipient)
         .add_attribute("amount", amount)
         .add_attribute("start_time", start_time.to_string())
         .add_attribute("end_time", end_time.to_string());
-
-    if refund > 0 {
-        let cw20 = Cw20Contract(config.cw20_addr);
-        let msg = cw20.call(Cw20ExecuteMsg::Transfer {
-            recipient: owner.into(),
-            amount: refund.into(),
-        })?;
-
-        response = response.add_message(msg);
-    }
     Ok(response)
 }
 
+pub fn execute_create_native_stream(
+    env: Env,
+    deps: DepsMut,
+    info: MessageInfo,
+    recipient: String,
+    start_time: u64,
+    end_time: u64,
+    schedule: Schedule,
+) -> Result<Response, ContractError> {
+    let coin = match info.funds.as_slice() {
+        [coin] if !coin.amount.is_zero() => coin.clone(),
+        _ => return Err(ContractError::InvalidFunds {}),
+    };
+    execute_create_stream(
+        env,
+        deps,
+        StreamParams {
+            owner: info.sender.into(),
+            recipient,
+            denom: Denom::Native(coin.denom),
+            amount: coin.amount,
+            start_time,
+            end_time,
+            schedule,
+        },
+    )
+}
+
 pub fn execute_receive(
     env: Env,
     deps: DepsMut,
     info: MessageInfo,
     wrapped: Cw20ReceiveMsg,
 ) -> Result<Response, ContractError> {
-    let config = CONFIG.load(deps.storage)?;
-    if config.cw20_addr != info.sender {
-        return Err(ContractError::Unauthorized {});
-    }
-
+    // any cw20 token can be streamed, the sending contract is the token
     let msg: ReceiveMsg = from_binary(&wrapped.msg)?;
     match msg {
         ReceiveMsg::CreateStream {
             start_time,
             end_time,
             recipient,
+            schedule,
         } => execute_create_stream(
             env,
             deps,
-            config,
             StreamParams {
                 owner: wrapped.sender,
                 recipient,
+                denom: Denom::Cw20(info.sender),
                 amount: wrapped.amount,
                 start_time,
                 end_time,
+                schedule: schedule.unwrap_or_default(),
             },
         ),
     }
 }
 
-pub fn execute_withdraw(
-    env: Env,
+fn vested_amount(stream: &Stream, block_time: u64) -> Uint128 {
+    // time spent paused does not count, so the schedule is evaluated as if the stream had
+    // never been paused, with its original end time
+    let block_time = stream
+        .paused_at
+        .unwrap_or(block_time)
+        .saturating_sub(stream.paused_duration);
+    let end_time = stream.end_time - stream.paused_duration;
+    let now = std::cmp::min(block_time, end_time);
+    let duration = end_time - stream.start_time;
+    let linear = || {
+        let elapsed = now.saturating_sub(stream.start_time);
+        stream.amount.multiply_ratio(elapsed, duration)
+    };
+    match &stream.schedule {
+        Schedule::Linear => linear(),
+        Schedule::Cliff { cliff_time } => {
+            if block_time < *cliff_time {
+                Uint128::zero()
+            } else {
+                linear()
+            }
+        }
+        Schedule::Stepwise { interval } => {
+            let steps = (duration + interval - 1) / interval;
+            let steps_passed = if now >= end_time {
+                steps
+            } else {
+                now.saturating_sub(stream.start_time) / interval
+            };
+            stream.amount.multiply_ratio(steps_passed, steps)
+        }
+        Schedule::Piecewise { points } => {
+            let mut last = SchedulePoint {
+                time: stream.start_time,
+                vested: Decimal::zero(),
+            };
+            for point in points {
+                if now < point.time {
+                    // interpolate between the points around now
+                    let elapsed = now.saturating_sub(last.time);
+                    let share = last.vested
+                        + (point.vested - last.vested)
+                            * Decimal::from_ratio(elapsed, point.time - last.time);
+                    return stream.amount * share;
+                }
+                last = point.clone();
+            }
+            stream.amount * last.vested
+        }
+    }
+}
+
+/// claim marks amount (or everything vested if None) as claimed by the recipient,
+/// returning the stream and the amount to pay out
+fn claim(
+    env: &Env,
     deps: DepsMut,
-    info: MessageInfo,
+    info: &MessageInfo,
     id: u64,
-) -> Result<Response, ContractError> {
-    let mut stream = STREAMS
+    amount: Option<Uint128>,
+) -> Result<(Stream, Uint128), ContractError> {
+    let mut stream = streams()
         .may_load(deps.storage, id)?
         .ok_or(ContractError::StreamNotFound {})?;
 
@@ -179,46 +350,300 @@ pub fn execute_withdraw(
         });
     }
 
+    if stream.cancelled {
+        return Err(ContractError::StreamCancelled {});
+    }
+
     if stream.claimed_amount >= stream.amount {
         return Err(ContractError::StreamFullyClaimed {});
     }
 
-    let block_time = env.block.time.seconds();
-    let time_passed = std::cmp::min(block_time, stream.end_time).saturating_sub(stream.start_time);
-    let vested = Uint128::from(time_passed) * stream.rate_per_second;
-    let released = vested - stream.claimed_amount;
+    let vested = vested_amount(&stream, env.block.time.seconds());
+    let available = vested - stream.claimed_amount;
 
-    if released.u128() == 0 {
+    if available.u128() == 0 {
         return Err(ContractError::NoFundsToClaim {});
     }
 
-    stream.claimed_amount = vested;
+    let released = match amount {
+        Some(amount) if amount > available => {
+            return Err(ContractError::AmountExceedsVested { available })
+        }
+        Some(amount) if amount.is_zero() => return Err(ContractError::NoFundsToClaim {}),
+        Some(amount) => amount,
+        None => available,
+    };
 
-    STREAMS.save(deps.storage, id, &stream)?;
+    stream.claimed_amount += released;
 
-    let config = CONFIG.load(deps.storage)?;
-    let cw20 = Cw20Contract(config.cw20_addr);
-    let msg = cw20.call(Cw20ExecuteMsg::Transfer {
-        recipient: stream.recipient.clone().into(),
-        amount: released,
-    })?;
+    streams().save(deps.storage, id, &stream)?;
+    Ok((stream, released))
+}
+
+pub fn execute_withdraw(
+    env: Env,
+    deps: DepsMut,
+    info: MessageInfo,
+    id: u64,
+    amount: Option<Uint128>,
+    to: Option<String>,
+) -> Result<Response, ContractError> {
+    let to = to.map(|to| deps.api.addr_validate(&to)).transpose()?;
+    let (stream, released) = claim(&env, deps, &info, id, amount)?;
+    let to = to.unwrap_or_else(|| stream.recipient.clone());
+
+    let msg = payout(&stream.denom, &to, released)?;
 
     let res = Response::new()
         .add_attribute("method", "withdraw")
         .add_attribute("stream_id", id.to_string())
         .add_attribute("amount", released)
         .add_attribute("recipient", stream.recipient)
+        .add_attribute("to", to)
+        .add_message(msg);
+    Ok(res)
+}
+
+pub fn execute_withdraw_and_send(
+    env: Env,
+    deps: DepsMut,
+    info: MessageInfo,
+    id: u64,
+    amount: Option<Uint128>,
+    contract: String,
+    msg: Binary,
+) -> Result<Response, ContractError> {
+    let contract = deps.api.addr_validate(&contract)?;
+    let (stream, released) = claim(&env, deps, &info, id, amount)?;
+
+    let msg: CosmosMsg = match &stream.denom {
+        // never execute msg as is, the contract could be any token this contract holds
+        Denom::Native(denom) => StreamReceiveMsg {
+            sender: info.sender.to_string(),
+            stream_id: id,
+            amount: released,
+            msg,
+        }
+        .into_cosmos_msg(&contract, coins(released.u128(), denom))?,
+        Denom::Cw20(addr) => Cw20Contract(addr.clone()).call(Cw20ExecuteMsg::Send {
+            contract: contract.to_string(),
+            amount: released,
+            msg,
+        })?,
+    };
+
+    let res = Response::new()
+        .add_attribute("method", "withdraw_and_send")
+        .add_attribute("stream_id", id.to_string())
+        .add_attribute("amount", released)
+        .add_attribute("recipient", stream.recipient)
+        .add_attribute("to", contract)
         .add_message(msg);
     Ok(res)
 }
 
+pub fn execute_cancel_stream(
+    env: Env,
+    deps: DepsMut,
+    info: MessageInfo,
+    id: u64,
+) -> Result<Response, ContractError> {
+    let mut stream = streams()
+        .may_load(deps.storage, id)?
+        .ok_or(ContractError::StreamNotFound {})?;
+
+    if stream.owner != info.sender {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    if stream.cancelled {
+        return Err(ContractError::StreamCancelled {});
+    }
+
+    if stream.claimed_amount >= stream.amount {
+        return Err(ContractError::StreamFullyClaimed {});
+    }
+
+    // The recipient keeps what has vested, the owner gets back the rest
+    let vested = vested_amount(&stream, env.block.time.seconds());
+    let released = vested - stream.claimed_amount;
+    let refund = stream.amount - vested;
+
+    stream.claimed_amount = vested;
+    stream.cancelled = true;
+    stream.paused_at = None;
+
+    streams().save(deps.storage, id, &stream)?;
+    STREAM_COUNT.update(deps.storage, |count| -> StdResult<_> { Ok(count - 1) })?;
+
+    let mut msgs = vec![];
+    if !released.is_zero() {
+        msgs.push(payout(&stream.denom, &stream.recipient, released)?);
+    }
+    if !refund.is_zero() {
+        msgs.push(payout(&stream.denom, &stream.owner, refund)?);
+    }
+
+    let res = Response::new()
+        .add_attribute("method", "cancel_stream")
+        .add_attribute("stream_id", id.to_string())
+        .add_attribute("released", released)
+        .add_attribute("refund", refund)
+        .add_attribute("recipient", stream.recipient)
+        .add_attribute("owner", stream.owner)
+        .add_messages(msgs);
+    Ok(res)
+}
+
+pub fn execute_pause_stream(
+    env: Env,
+    deps: DepsMut,
+    info: MessageInfo,
+    id: u64,
+) -> Result<Response, ContractError> {
+    let mut stream = streams()
+        .may_load(deps.storage, id)?
+        .ok_or(ContractError::StreamNotFound {})?;
+
+    if stream.owner != info.sender {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    if stream.cancelled {
+        return Err(ContractError::StreamCancelled {});
+    }
+
+    if stream.paused_at.is_some() {
+        return Err(ContractError::StreamPaused {});
+    }
+
+    let block_time = env.block.time.seconds();
+    if block_time >= stream.end_time {
+        return Err(ContractError::StreamEnded {});
+    }
+
+    stream.paused_at = Some(block_time);
+    streams().save(deps.storage, id, &stream)?;
+
+    let res = Response::new()
+        .add_attribute("method", "pause_stream")
+        .add_attribute("stream_id", id.to_string())
+        .add_attribute("paused_at", block_time.to_string());
+    Ok(res)
+}
+
+pub fn execute_resume_stream(
+    env: Env,
+    deps: DepsMut,
+    info: MessageInfo,
+    id: u64,
+) -> Result<Response, ContractError> {
+    let mut stream = streams()
+        .may_load(deps.storage, id)?
+        .ok_or(ContractError::StreamNotFound {})?;
+
+    if stream.owner != info.sender {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    if stream.cancelled {
+        return Err(ContractError::StreamCancelled {});
+    }
+
+    let paused_at = stream.paused_at.ok_or(ContractError::StreamNotPaused {})?;
+
+    // the stream ends as much later as it was paused, not counting time before it started.
+    // It cannot end while paused, so the end time does not limit the pause
+    let pause = env
+        .block
+        .time
+        .seconds()
+        .saturating_sub(std::cmp::max(paused_at, stream.start_time));
+    stream.paused_at = None;
+    stream.paused_duration += pause;
+    stream.end_time += pause;
+    streams().save(deps.storage, id, &stream)?;
+
+    let res = Response::new()
+        .add_attribute("method", "resume_stream")
+        .add_attribute("stream_id", id.to_string())
+        .add_attribute("paused_duration", stream.paused_duration.to_string())
+        .add_attribute("end_time", stream.end_time.to_string());
+    Ok(res)
+}
+
+/// stream ids are used as cw721 token ids
+fn parse_token_id(token_id: &str) -> Result<u64, ContractError> {
+    token_id
+        .parse()
+        .map_err(|_| ContractError::StreamNotFound {})
+}
+
+pub fn execute_transfer_stream(
+    deps: DepsMut,
+    info: MessageInfo,
+    id: u64,
+    new_recipient: String,
+) -> Result<Response, ContractError> {
+    let mut stream = streams()
+        .may_load(deps.storage, id)?
+        .ok_or(ContractError::StreamNotFound {})?;
+
+    if stream.recipient != info.sender {
+        return Err(ContractError::NotStreamRecipient {
+            recipient: stream.recipient,
+        });
+    }
+
+    if stream.cancelled {
+        return Err(ContractError::StreamCancelled {});
+    }
+
+    stream.recipient = deps.api.addr_validate(&new_recipient)?;
+    streams().save(deps.storage, id, &stream)?;
+
+    let res = Response::new()
+        .add_attribute("method", "transfer_stream")
+        .add_attribute("stream_id", id.to_string())
+        .add_attribute("from", info.sender)
+        .add_attribute("recipient", stream.recipient);
+    Ok(res)
+}
+
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> StdResult<Binary> {
     match msg {
         QueryMsg::GetConfig {} => to_binary(&query_config(deps)?),
         QueryMsg::GetStream { id } => to_binary(&query_stream(deps, id)?),
-        QueryMsg::ListStreams { start, limit } => {
-            to_binary(&query_list_streams(deps, start, limit)?)
+        QueryMsg::ListStreams { start_after, limit } => {
+            to_binary(&query_list_streams(deps, start_after, limit)?)
+        }
+        QueryMsg::StreamsByOwner {
+            owner,
+            start_after,
+            limit,
+        } => to_binary(&query_streams_by_owner(deps, owner, start_after, limit)?),
+        QueryMsg::StreamsByRecipient {
+            recipient,
+            start_after,
+            limit,
+        } => to_binary(&query_streams_by_recipient(
+            deps,
+            recipient,
+            start_after,
+            limit,
+        )?),
+        QueryMsg::OwnerOf { token_id, .. } => to_binary(&query_owner_of(deps, token_id)?),
+        QueryMsg::NumTokens {} => to_binary(&NumTokensResponse {
+            count: STREAM_COUNT.load(deps.storage)?,
+        }),
+        QueryMsg::Tokens {
+            owner,
+            start_after,
+            limit,
+        } => to_binary(&query_tokens(deps, Some(owner), start_after, limit)?),
+        QueryMsg::AllTokens { start_after, limit } => {
+            to_binary(&query_tokens(deps, None, start_after, limit)?)
         }
     }
 }
@@ -227,61 +652,182 @@ fn query_config(deps: Deps) -> StdResult<ConfigResponse> {
     let config = CONFIG.load(deps.storage)?;
     Ok(ConfigResponse {
         owner: config.owner.into(),
-        cw20_addr: config.cw20_addr.into(),
     })
 }
 
 fn query_stream(deps: Deps, id: u64) -> StdResult<StreamResponse> {
-    let stream = STREAMS.load(deps.storage, id)?;
-    Ok(StreamResponse {
-        id,
-        owner: stream.owner.into(),
-        recipient: stream.recipient.into(),
-        amount: stream.amount,
-        claimed_amount: stream.claimed_amount,
-        rate_per_second: stream.rate_per_second,
-        start_time: stream.start_time,
-        end_time: stream.end_time,
+    let stream = streams().load(deps.storage, id)?;
+    Ok(stream_response(id, stream))
+}
+
+fn query_owner_of(deps: Deps, token_id: String) -> StdResult<OwnerOfResponse> {
+    let id = token_id
+        .parse()
+        .map_err(|_| StdError::not_found("stream"))?;
+    let stream = streams().load(deps.storage, id)?;
+    Ok(OwnerOfResponse {
+        owner: stream.recipient.into(),
+        // streams cannot be approved
+        approvals: vec![],
     })
 }
 
+fn query_tokens(
+    deps: Deps,
+    owner: Option<String>,
+    start_after: Option<String>,
+    limit: Option<u32>,
+) -> StdResult<TokensResponse> {
+    let start = start_after
+        .map(|id| id.parse::<u64>().map_err(|_| StdError::not_found("stream")))
+        .transpose()?
+        .map(Bound::exclusive);
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+
+    // the cw721 owner of a stream is its recipient
+    let ids = match owner {
+        Some(owner) => streams()
+            .idx
+            .recipient
+            .prefix(deps.api.addr_validate(&owner)?)
+            .keys(deps.storage, start, None, Order::Ascending)
+            .take(limit)
+            .collect::<StdResult<Vec<_>>>()?,
+        None => streams()
+            .keys(deps.storage, start, None, Order::Ascending)
+            .take(limit)
+            .collect::<StdResult<Vec<_>>>()?,
+    };
+    let tokens = ids.into_iter().map(|id| id.to_string()).collect();
+    Ok(TokensResponse { tokens })
+}
+
 fn query_list_streams(
     deps: Deps,
-    start: Option<u8>,
-    limit: Option<u8>,
+    start_after: Option<u64>,
+    limit: Option<u32>,
+) -> StdResult<ListStreamsResponse> {
+    let start = start_after.map(Bound::exclusive);
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+
+    let streams = streams()
+        .range(deps.storage, start, None, Order::Ascending)
+        .take(limit)
+        .map(map_stream)
+        .collect::<StdResult<Vec<_>>>()?;
+    Ok(ListStreamsResponse { streams })
+}
+
+fn query_streams_by_owner(
+    deps: Deps,
+    owner: String,
+    start_after: Option<u64>,
+    limit: Option<u32>,
+) -> StdResult<ListStreamsResponse> {
+    let owner = deps.api.addr_validate(&owner)?;
+    let start = start_after.map(Bound::exclusive);
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+
+    let streams = streams()
+        .idx
+        .owner
+        .prefix(owner)
+        .range(deps.storage, start, None, Order::Ascending)
+        .take(limit)
+        .map(map_stream)
+        .collect::<StdResult<Vec<_>>>()?;
+    Ok(ListStreamsResponse { streams })
+}
+
+fn query_streams_by_recipient(
+    deps: Deps,
+    recipient: String,
+    start_after: Option<u64>,
+    limit: Option<u32>,
 ) -> StdResult<ListStreamsResponse> {
-    let start = start.map(Bound::inclusive);
-    let limit = limit.unwrap_or(5);
+    let recipient = deps.api.addr_validate(&recipient)?;
+    let start = start_after.map(Bound::exclusive);
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
 
-    let streams = STREAMS
+    let streams = streams()
+        .idx
+        .recipient
+        .prefix(recipient)
         .range(deps.storage, start, None, Order::Ascending)
-        .take(limit.into())
+        .take(limit)
         .map(map_stream)
         .collect::<StdResult<Vec<_>>>()?;
     Ok(ListStreamsResponse { streams })
 }
 
 fn map_stream(item: StdResult<(u64, Stream)>) -> StdResult<StreamResponse> {
-    item.map(|(id, stream)| StreamResponse {
+    item.map(|(id, stream)| stream_response(id, stream))
+}
+
+fn stream_response(id: u64, stream: Stream) -> StreamResponse {
+    // the average rate while not paused, rounded down
+    let duration = stream.end_time - stream.start_time - stream.paused_duration;
+    let rate_per_second = stream.amount / Uint128::from(duration);
+    StreamResponse {
         id,
-        owner: stream.owner.to_string(),
-        recipient: stream.recipient.to_string(),
+        owner: stream.owner.into(),
+        recipient: stream.recipient.into(),
+        denom: stream.denom,
         amount: stream.amount,
         claimed_amount: stream.claimed_amount,
         start_time: stream.start_time,
         end_time: stream.end_time,
-        rate_per_second: stream.rate_per_second,
-    })
+        rate_per_second,
+        schedule: stream.schedule,
+        cancelled: stream.cancelled,
+        paused: stream.paused_at.is_some(),
+        paused_at: stream.paused_at,
+        paused_duration: stream.paused_duration,
+    }
 }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use cosmwasm_std::testing::{mock_dependencies, mock_env, mock_info};
-    use cosmwasm_std::{Addr, CosmosMsg, WasmMsg};
-
-    fn get_stream(deps: Deps, id: u64) -> Stream {
-        let msg = QueryMsg::GetStream { id };
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn migrate(mut deps: DepsMut, _env: Env, msg: MigrateMsg) -> Result<Response, ContractError> {
+    let contract_info = get_contract_version(deps.storage)?;
+    if contract_info.contract != CONTRACT_NAME {
+        return Err(ContractError::CannotMigrate {
+            previous_contract: contract_info.contract,
+        });
+    }
+    let contract_version: Version = contract_info.version.parse()?;
+    let current_version: Version = CONTRACT_VERSION.parse()?;
+    if contract_version > current_version {
+        return Err(ContractError::CannotMigrateVersion {
+            previous_version: contract_info.version,
+        });
+    }
+
+    set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
+    let mut res = Response::new().add_attribute("method", "migrate");
+    // streams are only rewritten once, a batch per migrate call
+    if contract_version <= Version::new(0, 14, 2) {
+        v0_14_2::migrate_config(deps.branch())?;
+    }
+    if v0_14_2::migrating_streams(deps.storage)? {
+        let limit = msg.limit.unwrap_or(DEFAULT_MIGRATE_LIMIT);
+        let done = v0_14_2::migrate_streams(deps, limit)?;
+        res = res.add_attribute("streams_migrated", done.to_string());
+    }
+    Ok(res)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use cosmwasm_std::testing::{mock_dependencies, mock_env, mock_info};
+    use cosmwasm_std::{attr, coin, Storage, Timestamp, WasmMsg};
+    use cw_storage_plus::Map;
+
+    // the primary storage of streams(), to write streams in older formats
+    const RAW_STREAMS: Map<u64, Stream> = Map::new("stream");
+
+    fn get_stream(deps: Deps, id: u64) -> Stream {
+        let msg = QueryMsg::GetStream { id };
         let res = query(deps, mock_env(), msg).unwrap();
         from_binary(&res).unwrap()
     }
@@ -289,10 +835,7 @@ mod tests {
     #[test]
     fn initialization() {
         let mut deps = mock_dependencies();
-        let msg = InstantiateMsg {
-            owner: None,
-            cw20_addr: String::from("cw20"),
-        };
+        let msg = InstantiateMsg { owner: None };
 
         let info = mock_info("creator", &[]);
         instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
@@ -305,7 +848,6 @@ mod tests {
             config,
             Config {
                 owner: Addr::unchecked("creator"),
-                cw20_addr: Addr::unchecked("cw20")
             }
         );
     }
@@ -313,10 +855,7 @@ mod tests {
     #[test]
     fn execute_withdraw() {
         let mut deps = mock_dependencies();
-        let msg = InstantiateMsg {
-            owner: None,
-            cw20_addr: String::from("cw20"),
-        };
+        let msg = InstantiateMsg { owner: None };
         let info = mock_info("cw20", &[]);
         instantiate(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
 
@@ -334,6 +873,7 @@ mod tests {
                 recipient,
                 start_time,
                 end_time,
+                schedule: None,
             })
             .unwrap(),
         });
@@ -344,23 +884,35 @@ mod tests {
             Stream {
                 owner: Addr::unchecked("alice"),
                 recipient: Addr::unchecked("bob"),
+                denom: Denom::Cw20(Addr::unchecked("cw20")),
                 amount,
                 claimed_amount: Uint128::new(0),
                 start_time,
-                rate_per_second: Uint128::new(1),
-                end_time
+                end_time,
+                schedule: Schedule::Linear,
+                cancelled: false,
+                paused_at: None,
+                paused_duration: 0,
             }
         );
 
         // Stream has not started
         let mut info = mock_info("owner", &[]);
         info.sender = Addr::unchecked("bob");
-        let msg = ExecuteMsg::Withdraw { id: 1 };
+        let msg = ExecuteMsg::Withdraw {
+            id: 1,
+            amount: None,
+            to: None,
+        };
         let err = execute(deps.as_mut(), env, info, msg).unwrap_err();
         assert_eq!(err, ContractError::NoFundsToClaim {});
 
         // Stream has started so tokens have vested
-        let msg = ExecuteMsg::Withdraw { id: 1 };
+        let msg = ExecuteMsg::Withdraw {
+            id: 1,
+            amount: None,
+            to: None,
+        };
         let mut info = mock_info("owner", &[]);
         let mut env = mock_env();
         info.sender = Addr::unchecked("bob");
@@ -386,11 +938,15 @@ mod tests {
             Stream {
                 owner: Addr::unchecked("alice"),
                 recipient: Addr::unchecked("bob"),
+                denom: Denom::Cw20(Addr::unchecked("cw20")),
                 amount,
                 claimed_amount: Uint128::new(50),
                 start_time,
-                rate_per_second: Uint128::new(1),
-                end_time
+                end_time,
+                schedule: Schedule::Linear,
+                cancelled: false,
+                paused_at: None,
+                paused_duration: 0,
             }
         );
 
@@ -400,7 +956,11 @@ mod tests {
         env.block.time = env.block.time.plus_seconds(500);
         let mut info = mock_info("owner", &[]);
         info.sender = Addr::unchecked("bob");
-        let msg = ExecuteMsg::Withdraw { id: 1 };
+        let msg = ExecuteMsg::Withdraw {
+            id: 1,
+            amount: None,
+            to: None,
+        };
         let res = execute(deps.as_mut(), env, info, msg).unwrap();
         let msg = res.messages[0].clone().msg;
 
@@ -419,12 +979,9 @@ mod tests {
     }
 
     #[test]
-    fn create_stream_with_refund() {
+    fn create_stream_without_refund() {
         let mut deps = mock_dependencies();
-        let msg = InstantiateMsg {
-            owner: None,
-            cw20_addr: String::from("cw20"),
-        };
+        let msg = InstantiateMsg { owner: None };
         let info = mock_info("cw20", &[]);
         instantiate(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
 
@@ -442,48 +999,62 @@ mod tests {
                 recipient,
                 start_time,
                 end_time,
+                schedule: None,
             })
             .unwrap(),
         });
 
-        // Make sure remaining funds were refunded if duration didn't divide evenly into amount
+        // The whole amount is streamed, even if duration doesn't divide evenly into it
         let res = execute(deps.as_mut(), env, info, msg).unwrap();
-        let refund_msg = res.messages[0].clone().msg;
-        assert_eq!(
-            refund_msg,
-            CosmosMsg::Wasm(WasmMsg::Execute {
-                contract_addr: String::from("cw20"),
-                msg: to_binary(&Cw20ExecuteMsg::Transfer {
-                    recipient: String::from("alice"),
-                    amount: Uint128::new(50)
-                })
-                .unwrap(),
-                funds: vec![]
-            })
-        );
+        assert!(res.messages.is_empty());
 
         assert_eq!(
             get_stream(deps.as_ref(), 1),
             Stream {
                 owner: Addr::unchecked("alice"),
                 recipient: Addr::unchecked("bob"),
-                amount: Uint128::new(300), // original amount - refund
+                denom: Denom::Cw20(Addr::unchecked("cw20")),
+                amount,
                 claimed_amount: Uint128::new(0),
                 start_time,
-                rate_per_second: Uint128::new(1),
-                end_time
+                end_time,
+                schedule: Schedule::Linear,
+                cancelled: false,
+                paused_at: None,
+                paused_duration: 0,
             }
         );
+        let stream = query_stream(deps.as_ref(), 1).unwrap();
+        assert_eq!(stream.rate_per_second, Uint128::new(1));
+
+        // Half of the time has passed
+        let mut env = mock_env();
+        env.block.time = env.block.time.plus_seconds(250);
+        let msg = ExecuteMsg::Withdraw {
+            id: 1,
+            amount: None,
+            to: None,
+        };
+        let res = execute(deps.as_mut(), env, mock_info("bob", &[]), msg).unwrap();
+        assert_eq!(
+            res.messages[0].clone().msg,
+            CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: String::from("cw20"),
+                msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                    recipient: String::from("bob"),
+                    amount: Uint128::new(175)
+                })
+                .unwrap(),
+                funds: vec![]
+            })
+        );
     }
 
     #[test]
     fn invalid_start_time() {
         let mut deps = mock_dependencies();
 
-        let msg = InstantiateMsg {
-            owner: None,
-            cw20_addr: String::from("cw20"),
-        };
+        let msg = InstantiateMsg { owner: None };
         let mut info = mock_info("alice", &[]);
         instantiate(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
 
@@ -500,6 +1071,7 @@ mod tests {
                 recipient,
                 start_time,
                 end_time,
+                schedule: None,
             })
             .unwrap(),
         });
@@ -509,19 +1081,213 @@ mod tests {
     }
 
     #[test]
-    fn invalid_cw20_addr() {
+    fn streams_in_many_tokens() {
         let mut deps = mock_dependencies();
+        let msg = InstantiateMsg { owner: None };
+        let info = mock_info("creator", &[]);
+        instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+        let start_time = mock_env().block.time.plus_seconds(100).seconds();
+        let end_time = mock_env().block.time.plus_seconds(200).seconds();
+        let create = to_binary(&ReceiveMsg::CreateStream {
+            recipient: String::from("bob"),
+            start_time,
+            end_time,
+            schedule: None,
+        })
+        .unwrap();
+
+        // Any cw20 token can be streamed
+        for token in ["cw20", "other-cw20"].iter() {
+            let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+                sender: String::from("alice"),
+                amount: Uint128::new(100),
+                msg: create.clone(),
+            });
+            execute(deps.as_mut(), mock_env(), mock_info(token, &[]), msg).unwrap();
+        }
+
+        // And native coins, but only one at a time
+        let msg = ExecuteMsg::CreateStream {
+            recipient: String::from("bob"),
+            start_time,
+            end_time,
+            schedule: None,
+        };
+        let info = mock_info("alice", &[coin(100, "uatom"), coin(100, "ujuno")]);
+        let err = execute(deps.as_mut(), mock_env(), info, msg.clone()).unwrap_err();
+        assert_eq!(err, ContractError::InvalidFunds {});
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("alice", &[]),
+            msg.clone(),
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::InvalidFunds {});
+        let info = mock_info("alice", &[coin(100, "uatom")]);
+        execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+        assert_eq!(
+            get_stream(deps.as_ref(), 3).denom,
+            Denom::Native(String::from("uatom"))
+        );
+
+        // Every stream pays out in its own token
+        let mut env = mock_env();
+        env.block.time = env.block.time.plus_seconds(150);
+        let mut payouts = vec![];
+        for id in 1..=3 {
+            let msg = ExecuteMsg::Withdraw {
+                id,
+                amount: None,
+                to: None,
+            };
+            let res = execute(deps.as_mut(), env.clone(), mock_info("bob", &[]), msg).unwrap();
+            payouts.push(res.messages[0].clone().msg);
+        }
+        let transfer = to_binary(&Cw20ExecuteMsg::Transfer {
+            recipient: String::from("bob"),
+            amount: Uint128::new(50),
+        })
+        .unwrap();
+        assert_eq!(
+            payouts,
+            vec![
+                CosmosMsg::Wasm(WasmMsg::Execute {
+                    contract_addr: String::from("cw20"),
+                    msg: transfer.clone(),
+                    funds: vec![]
+                }),
+                CosmosMsg::Wasm(WasmMsg::Execute {
+                    contract_addr: String::from("other-cw20"),
+                    msg: transfer,
+                    funds: vec![]
+                }),
+                CosmosMsg::Bank(BankMsg::Send {
+                    to_address: String::from("bob"),
+                    amount: coins(50, "uatom"),
+                }),
+            ]
+        );
+    }
+
+    #[test]
+    fn migrate_single_token_streams() {
+        let mut deps = mock_dependencies();
+        set_contract_version(&mut deps.storage, CONTRACT_NAME, "0.14.2").unwrap();
+        deps.storage
+            .set(b"config", br#"{"owner":"creator","cw20_addr":"cw20"}"#);
+        deps.storage.set(
+            &RAW_STREAMS.key(1),
+            br#"{"owner":"alice","recipient":"bob","amount":"200","claimed_amount":"50","start_time":100,"end_time":300,"rate_per_second":"1"}"#,
+        );
+        let cancelled = br#"{"owner":"alice","recipient":"carol","denom":{"native":"uatom"},"amount":"200","claimed_amount":"0","start_time":100,"end_time":300,"rate_per_second":"1","cancelled":true}"#;
+        deps.storage.set(&RAW_STREAMS.key(2), cancelled);
+        STREAM_SEQ.save(&mut deps.storage, &2).unwrap();
+        let num_tokens = |deps: Deps| -> u64 {
+            let res = query(deps, mock_env(), QueryMsg::NumTokens {}).unwrap();
+            from_binary::<NumTokensResponse>(&res).unwrap().count
+        };
+
+        // streams are rewritten in batches
+        let msg = MigrateMsg { limit: Some(1) };
+        let res = migrate(deps.as_mut(), mock_env(), msg.clone()).unwrap();
+        assert_eq!(res.attributes[1], attr("streams_migrated", "false"));
+        let version = get_contract_version(&deps.storage).unwrap();
+        assert_eq!(version.version, CONTRACT_VERSION);
+        streams().load(&deps.storage, 1).unwrap();
+        assert_eq!(
+            deps.storage.get(&RAW_STREAMS.key(2)).unwrap(),
+            cancelled.to_vec()
+        );
+        let res = migrate(deps.as_mut(), mock_env(), msg).unwrap();
+        assert_eq!(res.attributes[1], attr("streams_migrated", "true"));
+        assert!(streams().load(&deps.storage, 2).unwrap().cancelled);
+        assert_eq!(num_tokens(deps.as_ref()), 1);
+
+        // streams are only rewritten when migrating from an older version
+        let legacy = br#"{"owner":"alice","recipient":"bob","denom":{"native":"uatom"},"amount":"200","claimed_amount":"0","start_time":100,"end_time":300,"rate_per_second":"1"}"#;
+        deps.storage.set(&RAW_STREAMS.key(3), legacy);
+        STREAM_SEQ.save(&mut deps.storage, &3).unwrap();
+        let res = migrate(deps.as_mut(), mock_env(), MigrateMsg { limit: None }).unwrap();
+        assert_eq!(res.attributes.len(), 1);
+        assert_eq!(
+            deps.storage.get(&RAW_STREAMS.key(3)).unwrap(),
+            legacy.to_vec()
+        );
 
-        let msg = InstantiateMsg {
-            owner: None,
-            cw20_addr: String::from("cw20"),
+        // streams that still store their rate can be migrated again
+        set_contract_version(&mut deps.storage, CONTRACT_NAME, "0.14.2").unwrap();
+        migrate(deps.as_mut(), mock_env(), MigrateMsg { limit: None }).unwrap();
+        let stream = streams().load(&deps.storage, 3).unwrap();
+        assert_eq!(stream.denom, Denom::Native(String::from("uatom")));
+        let raw = deps.storage.get(&RAW_STREAMS.key(3)).unwrap();
+        assert!(!String::from_utf8(raw).unwrap().contains("rate_per_second"));
+        assert_eq!(num_tokens(deps.as_ref()), 2);
+
+        let config = CONFIG.load(&deps.storage).unwrap();
+        assert_eq!(config.owner, Addr::unchecked("creator"));
+        assert_eq!(
+            streams().load(&deps.storage, 1).unwrap(),
+            Stream {
+                owner: Addr::unchecked("alice"),
+                recipient: Addr::unchecked("bob"),
+                denom: Denom::Cw20(Addr::unchecked("cw20")),
+                amount: Uint128::new(200),
+                claimed_amount: Uint128::new(50),
+                start_time: 100,
+                end_time: 300,
+                schedule: Schedule::Linear,
+                cancelled: false,
+                paused_at: None,
+                paused_duration: 0,
+            }
+        );
+
+        // migrated streams are indexed by recipient
+        let msg = QueryMsg::StreamsByRecipient {
+            recipient: String::from("bob"),
+            start_after: None,
+            limit: None,
         };
+        let res: ListStreamsResponse =
+            from_binary(&query(deps.as_ref(), mock_env(), msg).unwrap()).unwrap();
+        let ids: Vec<u64> = res.streams.iter().map(|s| s.id).collect();
+        assert_eq!(ids, vec![1, 3]);
+
+        // cannot migrate from another contract
+        set_contract_version(&mut deps.storage, "crates.io:cw20-base", "0.16.0").unwrap();
+        let err = migrate(deps.as_mut(), mock_env(), MigrateMsg { limit: None }).unwrap_err();
+        assert_eq!(
+            err,
+            ContractError::CannotMigrate {
+                previous_contract: String::from("crates.io:cw20-base")
+            }
+        );
+
+        // cannot migrate from a newer version
+        set_contract_version(&mut deps.storage, CONTRACT_NAME, "9.0.0").unwrap();
+        let err = migrate(deps.as_mut(), mock_env(), MigrateMsg { limit: None }).unwrap_err();
+        assert_eq!(
+            err,
+            ContractError::CannotMigrateVersion {
+                previous_version: String::from("9.0.0")
+            }
+        );
+    }
+
+    #[test]
+    fn small_amount_stream() {
+        let mut deps = mock_dependencies();
+
+        let msg = InstantiateMsg { owner: None };
         let mut info = mock_info("alice", &[]);
         instantiate(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
 
         let sender = Addr::unchecked("alice").to_string();
         let recipient = Addr::unchecked("bob").to_string();
-        let amount = Uint128::new(100);
+        let amount = Uint128::new(3);
         let start_time = mock_env().block.time.plus_seconds(100).seconds();
         let end_time = mock_env().block.time.plus_seconds(200).seconds();
 
@@ -532,30 +1298,35 @@ mod tests {
                 recipient,
                 start_time,
                 end_time,
+                schedule: None,
             })
             .unwrap(),
         });
-        info.sender = Addr::unchecked("wrongCw20");
-        let err = execute(deps.as_mut(), mock_env(), info, msg).unwrap_err();
-        assert_eq!(err, ContractError::Unauthorized {});
+        info.sender = Addr::unchecked("cw20");
+        execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+        let stream = query_stream(deps.as_ref(), 1).unwrap();
+        assert_eq!(stream.rate_per_second, Uint128::zero());
+
+        // Rounded down while streaming, everything at the end
+        let stream = streams().load(&deps.storage, 1).unwrap();
+        assert_eq!(vested_amount(&stream, start_time + 50), Uint128::new(1));
+        assert_eq!(vested_amount(&stream, start_time + 67), Uint128::new(2));
+        assert_eq!(vested_amount(&stream, end_time), amount);
     }
 
     #[test]
-    fn invalid_deposit_amount() {
+    fn cancel_stream() {
         let mut deps = mock_dependencies();
-
-        let msg = InstantiateMsg {
-            owner: None,
-            cw20_addr: String::from("cw20"),
-        };
-        let mut info = mock_info("alice", &[]);
+        let msg = InstantiateMsg { owner: None };
+        let info = mock_info("cw20", &[]);
         instantiate(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
 
         let sender = Addr::unchecked("alice").to_string();
         let recipient = Addr::unchecked("bob").to_string();
-        let amount = Uint128::new(3);
-        let start_time = mock_env().block.time.plus_seconds(100).seconds();
-        let end_time = mock_env().block.time.plus_seconds(200).seconds();
+        let amount = Uint128::new(200);
+        let env = mock_env();
+        let start_time = env.block.time.plus_seconds(100).seconds();
+        let end_time = env.block.time.plus_seconds(300).seconds();
 
         let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
             sender,
@@ -564,11 +1335,745 @@ mod tests {
                 recipient,
                 start_time,
                 end_time,
+                schedule: None,
             })
             .unwrap(),
         });
-        info.sender = Addr::unchecked("cw20");
-        let err = execute(deps.as_mut(), mock_env(), info, msg).unwrap_err();
-        assert_eq!(err, ContractError::AmountLessThanDuration {});
+        execute(deps.as_mut(), env, info, msg).unwrap();
+
+        let mut env = mock_env();
+        env.block.time = env.block.time.plus_seconds(150);
+
+        // Only the owner can cancel
+        let info = mock_info("bob", &[]);
+        let msg = ExecuteMsg::CancelStream { id: 1 };
+        let err = execute(deps.as_mut(), env.clone(), info, msg).unwrap_err();
+        assert_eq!(err, ContractError::Unauthorized {});
+        let num_tokens = |deps: Deps| -> u64 {
+            let res = query(deps, mock_env(), QueryMsg::NumTokens {}).unwrap();
+            from_binary::<NumTokensResponse>(&res).unwrap().count
+        };
+        assert_eq!(num_tokens(deps.as_ref()), 1);
+
+        // Bob gets what has vested, Alice gets back the rest
+        let info = mock_info("alice", &[]);
+        let msg = ExecuteMsg::CancelStream { id: 1 };
+        let res = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+        let msgs: Vec<_> = res.messages.into_iter().map(|m| m.msg).collect();
+        assert_eq!(
+            msgs,
+            vec![
+                CosmosMsg::Wasm(WasmMsg::Execute {
+                    contract_addr: String::from("cw20"),
+                    msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                        recipient: String::from("bob"),
+                        amount: Uint128::new(50)
+                    })
+                    .unwrap(),
+                    funds: vec![]
+                }),
+                CosmosMsg::Wasm(WasmMsg::Execute {
+                    contract_addr: String::from("cw20"),
+                    msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                        recipient: String::from("alice"),
+                        amount: Uint128::new(150)
+                    })
+                    .unwrap(),
+                    funds: vec![]
+                }),
+            ]
+        );
+
+        assert_eq!(
+            get_stream(deps.as_ref(), 1),
+            Stream {
+                owner: Addr::unchecked("alice"),
+                recipient: Addr::unchecked("bob"),
+                denom: Denom::Cw20(Addr::unchecked("cw20")),
+                amount,
+                claimed_amount: Uint128::new(50),
+                start_time,
+                end_time,
+                schedule: Schedule::Linear,
+                cancelled: true,
+                paused_at: None,
+                paused_duration: 0,
+            }
+        );
+        // Cancelled streams are not counted anymore
+        assert_eq!(num_tokens(deps.as_ref()), 0);
+
+        // Nothing more can be withdrawn or cancelled
+        env.block.time = env.block.time.plus_seconds(500);
+        let msg = ExecuteMsg::Withdraw {
+            id: 1,
+            amount: None,
+            to: None,
+        };
+        let err = execute(deps.as_mut(), env.clone(), mock_info("bob", &[]), msg).unwrap_err();
+        assert_eq!(err, ContractError::StreamCancelled {});
+        let msg = ExecuteMsg::CancelStream { id: 1 };
+        let err = execute(deps.as_mut(), env, info, msg).unwrap_err();
+        assert_eq!(err, ContractError::StreamCancelled {});
+    }
+
+    #[test]
+    fn pause_and_resume_stream() {
+        let mut deps = mock_dependencies();
+        let msg = InstantiateMsg { owner: None };
+        instantiate(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg).unwrap();
+
+        let now = mock_env().block.time.seconds();
+        let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+            sender: String::from("alice"),
+            amount: Uint128::new(200),
+            msg: to_binary(&ReceiveMsg::CreateStream {
+                recipient: String::from("bob"),
+                start_time: now + 100,
+                end_time: now + 300,
+                schedule: None,
+            })
+            .unwrap(),
+        });
+        execute(deps.as_mut(), mock_env(), mock_info("cw20", &[]), msg).unwrap();
+
+        let at = |offset: u64| {
+            let mut env = mock_env();
+            env.block.time = Timestamp::from_seconds(now + offset);
+            env
+        };
+        let withdraw = ExecuteMsg::Withdraw {
+            id: 1,
+            amount: None,
+            to: None,
+        };
+        let stream_response = |deps: Deps| -> StreamResponse {
+            let res = query(deps, mock_env(), QueryMsg::GetStream { id: 1 }).unwrap();
+            from_binary(&res).unwrap()
+        };
+
+        // Only the owner can pause
+        let msg = ExecuteMsg::PauseStream { id: 1 };
+        let err = execute(deps.as_mut(), at(150), mock_info("bob", &[]), msg.clone()).unwrap_err();
+        assert_eq!(err, ContractError::Unauthorized {});
+        execute(deps.as_mut(), at(150), mock_info("alice", &[]), msg.clone()).unwrap();
+        let err = execute(deps.as_mut(), at(160), mock_info("alice", &[]), msg).unwrap_err();
+        assert_eq!(err, ContractError::StreamPaused {});
+
+        let res = stream_response(deps.as_ref());
+        assert!(res.paused);
+        assert_eq!(res.paused_at, Some(now + 150));
+        assert_eq!(res.paused_duration, 0);
+
+        // Nothing vests while paused, but what vested before can be withdrawn
+        let res = execute(
+            deps.as_mut(),
+            at(250),
+            mock_info("bob", &[]),
+            withdraw.clone(),
+        )
+        .unwrap();
+        assert_eq!(res.attributes[2], attr("amount", "50"));
+
+        // Resuming pushes back the end time
+        let msg = ExecuteMsg::ResumeStream { id: 1 };
+        let err = execute(deps.as_mut(), at(250), mock_info("bob", &[]), msg.clone()).unwrap_err();
+        assert_eq!(err, ContractError::Unauthorized {});
+        execute(deps.as_mut(), at(250), mock_info("alice", &[]), msg.clone()).unwrap();
+        let err = execute(deps.as_mut(), at(260), mock_info("alice", &[]), msg).unwrap_err();
+        assert_eq!(err, ContractError::StreamNotPaused {});
+
+        let res = stream_response(deps.as_ref());
+        assert!(!res.paused);
+        assert_eq!(res.paused_at, None);
+        assert_eq!(res.paused_duration, 100);
+        assert_eq!(res.end_time, now + 400);
+        assert_eq!(res.rate_per_second, Uint128::new(1));
+
+        let res = execute(
+            deps.as_mut(),
+            at(300),
+            mock_info("bob", &[]),
+            withdraw.clone(),
+        )
+        .unwrap();
+        assert_eq!(res.attributes[2], attr("amount", "50"));
+        let res = execute(deps.as_mut(), at(400), mock_info("bob", &[]), withdraw).unwrap();
+        assert_eq!(res.attributes[2], attr("amount", "100"));
+
+        // Ended streams cannot be paused
+        let msg = ExecuteMsg::PauseStream { id: 1 };
+        let err = execute(deps.as_mut(), at(400), mock_info("alice", &[]), msg).unwrap_err();
+        assert_eq!(err, ContractError::StreamEnded {});
+    }
+
+    #[test]
+    fn pause_before_start_and_cancel_while_paused() {
+        let mut deps = mock_dependencies();
+        let msg = InstantiateMsg { owner: None };
+        instantiate(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg).unwrap();
+
+        let now = mock_env().block.time.seconds();
+        for _ in 0..2 {
+            let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+                sender: String::from("alice"),
+                amount: Uint128::new(200),
+                msg: to_binary(&ReceiveMsg::CreateStream {
+                    recipient: String::from("bob"),
+                    start_time: now + 100,
+                    end_time: now + 300,
+                    schedule: None,
+                })
+                .unwrap(),
+            });
+            execute(deps.as_mut(), mock_env(), mock_info("cw20", &[]), msg).unwrap();
+        }
+
+        let at = |offset: u64| {
+            let mut env = mock_env();
+            env.block.time = Timestamp::from_seconds(now + offset);
+            env
+        };
+        let stream_response = |deps: Deps, id: u64| -> StreamResponse {
+            let res = query(deps, mock_env(), QueryMsg::GetStream { id }).unwrap();
+            from_binary(&res).unwrap()
+        };
+        let alice = mock_info("alice", &[]);
+
+        // A pause that ends before the start does not delay the stream
+        execute(
+            deps.as_mut(),
+            at(10),
+            alice.clone(),
+            ExecuteMsg::PauseStream { id: 1 },
+        )
+        .unwrap();
+        execute(
+            deps.as_mut(),
+            at(50),
+            alice.clone(),
+            ExecuteMsg::ResumeStream { id: 1 },
+        )
+        .unwrap();
+        let res = stream_response(deps.as_ref(), 1);
+        assert_eq!(res.paused_duration, 0);
+        assert_eq!(res.end_time, now + 300);
+
+        // A pause over the start only counts from the start
+        execute(
+            deps.as_mut(),
+            at(60),
+            alice.clone(),
+            ExecuteMsg::PauseStream { id: 1 },
+        )
+        .unwrap();
+        execute(
+            deps.as_mut(),
+            at(150),
+            alice.clone(),
+            ExecuteMsg::ResumeStream { id: 1 },
+        )
+        .unwrap();
+        let res = stream_response(deps.as_ref(), 1);
+        assert_eq!(res.paused_duration, 50);
+        assert_eq!(res.end_time, now + 350);
+        assert_eq!(
+            vested_amount(&get_stream(deps.as_ref(), 1), now + 200),
+            Uint128::new(50)
+        );
+
+        // Cancelling a paused stream settles what vested before the pause and ends the pause
+        execute(
+            deps.as_mut(),
+            at(150),
+            alice.clone(),
+            ExecuteMsg::PauseStream { id: 2 },
+        )
+        .unwrap();
+        let res = execute(
+            deps.as_mut(),
+            at(250),
+            alice,
+            ExecuteMsg::CancelStream { id: 2 },
+        )
+        .unwrap();
+        assert_eq!(res.attributes[2], attr("released", "50"));
+        let res = stream_response(deps.as_ref(), 2);
+        assert!(res.cancelled);
+        assert!(!res.paused);
+        assert_eq!(res.paused_at, None);
+    }
+
+    #[test]
+    fn vesting_schedules() {
+        let mut deps = mock_dependencies();
+        let msg = InstantiateMsg { owner: None };
+        instantiate(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg).unwrap();
+
+        let now = mock_env().block.time.seconds();
+        let start_time = now + 100;
+        let end_time = now + 300;
+        let create = |schedule: Schedule| {
+            ExecuteMsg::Receive(Cw20ReceiveMsg {
+                sender: String::from("alice"),
+                amount: Uint128::new(200),
+                msg: to_binary(&ReceiveMsg::CreateStream {
+                    recipient: String::from("bob"),
+                    start_time,
+                    end_time,
+                    schedule: Some(schedule),
+                })
+                .unwrap(),
+            })
+        };
+
+        // Invalid schedules are rejected
+        let invalid = vec![
+            Schedule::Cliff {
+                cliff_time: now + 50,
+            },
+            Schedule::Stepwise { interval: 0 },
+            Schedule::Piecewise {
+                points: vec![SchedulePoint {
+                    time: end_time,
+                    vested: Decimal::percent(90),
+                }],
+            },
+        ];
+        for schedule in invalid {
+            let err = execute(
+                deps.as_mut(),
+                mock_env(),
+                mock_info("cw20", &[]),
+                create(schedule),
+            )
+            .unwrap_err();
+            assert!(matches!(err, ContractError::InvalidSchedule { .. }));
+        }
+
+        let schedules = vec![
+            Schedule::Cliff {
+                cliff_time: now + 200,
+            },
+            Schedule::Stepwise { interval: 60 },
+            Schedule::Piecewise {
+                points: vec![
+                    SchedulePoint {
+                        time: now + 200,
+                        vested: Decimal::percent(25),
+                    },
+                    SchedulePoint {
+                        time: end_time,
+                        vested: Decimal::one(),
+                    },
+                ],
+            },
+        ];
+        for schedule in schedules {
+            execute(
+                deps.as_mut(),
+                mock_env(),
+                mock_info("cw20", &[]),
+                create(schedule),
+            )
+            .unwrap();
+        }
+
+        let vested = |id: u64, offset: u64| {
+            let stream = streams().load(&deps.storage, id).unwrap();
+            vested_amount(&stream, now + offset).u128()
+        };
+        // Cliff catches up with linear vesting
+        assert_eq!(vested(1, 150), 0);
+        assert_eq!(vested(1, 200), 100);
+        assert_eq!(vested(1, 250), 150);
+        // Four steps, the last one shorter
+        assert_eq!(vested(2, 159), 0);
+        assert_eq!(vested(2, 160), 50);
+        assert_eq!(vested(2, 280), 150);
+        assert_eq!(vested(2, 300), 200);
+        // Interpolated between the points
+        assert_eq!(vested(3, 150), 25);
+        assert_eq!(vested(3, 250), 125);
+        assert_eq!(vested(3, 400), 200);
+    }
+
+    #[test]
+    fn list_streams_by_owner_and_recipient() {
+        let mut deps = mock_dependencies();
+        let msg = InstantiateMsg { owner: None };
+        instantiate(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg).unwrap();
+
+        let start_time = mock_env().block.time.plus_seconds(100).seconds();
+        let end_time = mock_env().block.time.plus_seconds(300).seconds();
+        for id in 1..=300u64 {
+            let (owner, recipient) = if id % 3 == 0 {
+                ("alice", "carol")
+            } else {
+                ("dave", "bob")
+            };
+            let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+                sender: String::from(owner),
+                amount: Uint128::new(200),
+                msg: to_binary(&ReceiveMsg::CreateStream {
+                    recipient: String::from(recipient),
+                    start_time,
+                    end_time,
+                    schedule: None,
+                })
+                .unwrap(),
+            });
+            execute(deps.as_mut(), mock_env(), mock_info("cw20", &[]), msg).unwrap();
+        }
+
+        let list = |deps: Deps, msg: QueryMsg| -> Vec<u64> {
+            let res: ListStreamsResponse =
+                from_binary(&query(deps, mock_env(), msg).unwrap()).unwrap();
+            res.streams.iter().map(|s| s.id).collect()
+        };
+
+        // Paging goes past the first 255 streams
+        let ids = list(
+            deps.as_ref(),
+            QueryMsg::ListStreams {
+                start_after: Some(255),
+                limit: Some(3),
+            },
+        );
+        assert_eq!(ids, vec![256, 257, 258]);
+        let ids = list(
+            deps.as_ref(),
+            QueryMsg::ListStreams {
+                start_after: Some(298),
+                limit: None,
+            },
+        );
+        assert_eq!(ids, vec![299, 300]);
+        // The limit is capped
+        let ids = list(
+            deps.as_ref(),
+            QueryMsg::ListStreams {
+                start_after: None,
+                limit: Some(1000),
+            },
+        );
+        assert_eq!(ids.len(), MAX_LIMIT as usize);
+
+        let ids = list(
+            deps.as_ref(),
+            QueryMsg::StreamsByOwner {
+                owner: String::from("alice"),
+                start_after: Some(270),
+                limit: None,
+            },
+        );
+        assert_eq!(ids, vec![273, 276, 279, 282, 285, 288, 291, 294, 297, 300]);
+        let ids = list(
+            deps.as_ref(),
+            QueryMsg::StreamsByRecipient {
+                recipient: String::from("bob"),
+                start_after: None,
+                limit: Some(4),
+            },
+        );
+        assert_eq!(ids, vec![1, 2, 4, 5]);
+
+        // The index follows transfers
+        let msg = ExecuteMsg::TransferStream {
+            id: 3,
+            new_recipient: String::from("bob"),
+        };
+        execute(deps.as_mut(), mock_env(), mock_info("carol", &[]), msg).unwrap();
+        let ids = list(
+            deps.as_ref(),
+            QueryMsg::StreamsByRecipient {
+                recipient: String::from("bob"),
+                start_after: Some(2),
+                limit: Some(2),
+            },
+        );
+        assert_eq!(ids, vec![3, 4]);
+        let ids = list(
+            deps.as_ref(),
+            QueryMsg::StreamsByRecipient {
+                recipient: String::from("carol"),
+                start_after: None,
+                limit: Some(1),
+            },
+        );
+        assert_eq!(ids, vec![6]);
+    }
+
+    #[test]
+    fn transfer_stream() {
+        let mut deps = mock_dependencies();
+        let msg = InstantiateMsg { owner: None };
+        instantiate(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg).unwrap();
+
+        let start_time = mock_env().block.time.plus_seconds(100).seconds();
+        let end_time = mock_env().block.time.plus_seconds(300).seconds();
+        for _ in 0..2 {
+            let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+                sender: String::from("alice"),
+                amount: Uint128::new(200),
+                msg: to_binary(&ReceiveMsg::CreateStream {
+                    recipient: String::from("bob"),
+                    start_time,
+                    end_time,
+                    schedule: None,
+                })
+                .unwrap(),
+            });
+            execute(deps.as_mut(), mock_env(), mock_info("cw20", &[]), msg).unwrap();
+        }
+
+        // Only the recipient can transfer
+        let msg = ExecuteMsg::TransferStream {
+            id: 1,
+            new_recipient: String::from("carol"),
+        };
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("alice", &[]),
+            msg.clone(),
+        )
+        .unwrap_err();
+        assert_eq!(
+            err,
+            ContractError::NotStreamRecipient {
+                recipient: Addr::unchecked("bob")
+            }
+        );
+        execute(deps.as_mut(), mock_env(), mock_info("bob", &[]), msg).unwrap();
+
+        // Or send it to a contract, cw721 style
+        let msg = ExecuteMsg::SendNft {
+            contract: String::from("market"),
+            token_id: String::from("2"),
+            msg: Binary::from(b"sell".to_vec()),
+        };
+        let res = execute(deps.as_mut(), mock_env(), mock_info("bob", &[]), msg).unwrap();
+        let receive = Cw721ReceiveMsg {
+            sender: String::from("bob"),
+            token_id: String::from("2"),
+            msg: Binary::from(b"sell".to_vec()),
+        };
+        assert_eq!(
+            res.messages[0].clone().msg,
+            receive.into_cosmos_msg("market").unwrap()
+        );
+
+        let owner_of = |deps: Deps, token_id: &str| {
+            let msg = QueryMsg::OwnerOf {
+                token_id: token_id.to_string(),
+                include_expired: None,
+            };
+            let res: OwnerOfResponse = from_binary(&query(deps, mock_env(), msg).unwrap()).unwrap();
+            res.owner
+        };
+        assert_eq!(owner_of(deps.as_ref(), "1"), "carol");
+        assert_eq!(owner_of(deps.as_ref(), "2"), "market");
+
+        let msg = QueryMsg::Tokens {
+            owner: String::from("carol"),
+            start_after: None,
+            limit: None,
+        };
+        let res: TokensResponse =
+            from_binary(&query(deps.as_ref(), mock_env(), msg).unwrap()).unwrap();
+        assert_eq!(res.tokens, vec![String::from("1")]);
+        let msg = QueryMsg::AllTokens {
+            start_after: Some(String::from("1")),
+            limit: None,
+        };
+        let res: TokensResponse =
+            from_binary(&query(deps.as_ref(), mock_env(), msg).unwrap()).unwrap();
+        assert_eq!(res.tokens, vec![String::from("2")]);
+        let res: NumTokensResponse =
+            from_binary(&query(deps.as_ref(), mock_env(), QueryMsg::NumTokens {}).unwrap())
+                .unwrap();
+        assert_eq!(res.count, 2);
+
+        // The new recipient withdraws
+        let mut env = mock_env();
+        env.block.time = env.block.time.plus_seconds(150);
+        let msg = ExecuteMsg::Withdraw {
+            id: 1,
+            amount: None,
+            to: None,
+        };
+        let err = execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("bob", &[]),
+            msg.clone(),
+        )
+        .unwrap_err();
+        assert_eq!(
+            err,
+            ContractError::NotStreamRecipient {
+                recipient: Addr::unchecked("carol")
+            }
+        );
+        let res = execute(deps.as_mut(), env, mock_info("carol", &[]), msg).unwrap();
+        assert_eq!(
+            res.messages[0].clone().msg,
+            CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: String::from("cw20"),
+                msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                    recipient: String::from("carol"),
+                    amount: Uint128::new(50)
+                })
+                .unwrap(),
+                funds: vec![]
+            })
+        );
+    }
+
+    #[test]
+    fn partial_withdraw_to_other_address() {
+        let mut deps = mock_dependencies();
+        let msg = InstantiateMsg { owner: None };
+        instantiate(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg).unwrap();
+
+        let start_time = mock_env().block.time.plus_seconds(100).seconds();
+        let end_time = mock_env().block.time.plus_seconds(300).seconds();
+        let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+            sender: String::from("alice"),
+            amount: Uint128::new(200),
+            msg: to_binary(&ReceiveMsg::CreateStream {
+                recipient: String::from("bob"),
+                start_time,
+                end_time,
+                schedule: None,
+            })
+            .unwrap(),
+        });
+        execute(deps.as_mut(), mock_env(), mock_info("cw20", &[]), msg).unwrap();
+        let msg = ExecuteMsg::CreateStream {
+            recipient: String::from("bob"),
+            start_time,
+            end_time,
+            schedule: None,
+        };
+        let info = mock_info("alice", &[coin(200, "uatom")]);
+        execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+        let mut env = mock_env();
+        env.block.time = env.block.time.plus_seconds(150);
+        let info = mock_info("bob", &[]);
+
+        // Not more than has vested
+        let msg = ExecuteMsg::Withdraw {
+            id: 1,
+            amount: Some(Uint128::new(60)),
+            to: None,
+        };
+        let err = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap_err();
+        assert_eq!(
+            err,
+            ContractError::AmountExceedsVested {
+                available: Uint128::new(50)
+            }
+        );
+
+        // Part of it to a cold wallet
+        let msg = ExecuteMsg::Withdraw {
+            id: 1,
+            amount: Some(Uint128::new(20)),
+            to: Some(String::from("cold")),
+        };
+        let res = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+        assert_eq!(
+            res.messages[0].clone().msg,
+            CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: String::from("cw20"),
+                msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                    recipient: String::from("cold"),
+                    amount: Uint128::new(20)
+                })
+                .unwrap(),
+                funds: vec![]
+            })
+        );
+        assert_eq!(
+            get_stream(deps.as_ref(), 1).claimed_amount,
+            Uint128::new(20)
+        );
+
+        // The rest to a contract, with a hook
+        let hook = Binary::from(b"deposit".to_vec());
+        let msg = ExecuteMsg::WithdrawAndSend {
+            id: 1,
+            amount: None,
+            contract: String::from("vault"),
+            msg: hook.clone(),
+        };
+        let res = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+        assert_eq!(
+            res.messages[0].clone().msg,
+            CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: String::from("cw20"),
+                msg: to_binary(&Cw20ExecuteMsg::Send {
+                    contract: String::from("vault"),
+                    amount: Uint128::new(30),
+                    msg: hook.clone(),
+                })
+                .unwrap(),
+                funds: vec![]
+            })
+        );
+        assert_eq!(
+            get_stream(deps.as_ref(), 1).claimed_amount,
+            Uint128::new(50)
+        );
+
+        // Native coins are attached to a ReceiveStream call wrapping the hook
+        let msg = ExecuteMsg::WithdrawAndSend {
+            id: 2,
+            amount: Some(Uint128::new(10)),
+            contract: String::from("vault"),
+            msg: hook.clone(),
+        };
+        let res = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+        assert_eq!(
+            res.messages[0].clone().msg,
+            CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: String::from("vault"),
+                msg: StreamReceiveMsg {
+                    sender: String::from("bob"),
+                    stream_id: 2,
+                    amount: Uint128::new(10),
+                    msg: hook,
+                }
+                .into_binary()
+                .unwrap(),
+                funds: coins(10, "uatom"),
+            })
+        );
+
+        // The hook is never executed as is, so it cannot move the cw20 tokens of other streams
+        let drain = to_binary(&Cw20ExecuteMsg::Transfer {
+            recipient: String::from("bob"),
+            amount: Uint128::new(200),
+        })
+        .unwrap();
+        let msg = ExecuteMsg::WithdrawAndSend {
+            id: 2,
+            amount: Some(Uint128::new(10)),
+            contract: String::from("cw20"),
+            msg: drain.clone(),
+        };
+        let res = execute(deps.as_mut(), env, info, msg).unwrap();
+        let sent = match &res.messages[0].msg {
+            CosmosMsg::Wasm(WasmMsg::Execute { msg, .. }) => msg.clone(),
+            msg => panic!("unexpected message {:?}", msg),
+        };
+        assert_ne!(sent, drain);
+        assert!(String::from_utf8(sent.to_vec())
+            .unwrap()
+            .starts_with(r#"{"receive_stream":{"#));
+        assert!(from_binary::<Cw20ExecuteMsg>(&sent).is_err());
     }
 }
@@ -1,7 +1,7 @@
 use cosmwasm_std::StdError;
 use thiserror::Error;
 
-use cosmwasm_std::Addr;
+use cosmwasm_std::{Addr, Uint128};
 
 #[derive(Error, Debug, PartialEq)]
 pub enum ContractError {
@@ -23,15 +23,48 @@ pub enum ContractError {
     #[error("No tokens have vested for this stream.")]
     NoFundsToClaim {},
 
+    #[error("Only {available} vested tokens can be withdrawn")]
+    AmountExceedsVested { available: Uint128 },
+
     #[error("Stream does not exist.")]
     StreamNotFound {},
 
-    #[error("Amount must be greater than duration")]
-    AmountLessThanDuration {},
+    #[error("The stream has been cancelled")]
+    StreamCancelled {},
+
+    #[error("The stream is paused")]
+    StreamPaused {},
+
+    #[error("The stream is not paused")]
+    StreamNotPaused {},
+
+    #[error("The stream has ended")]
+    StreamEnded {},
 
     #[error("Stream recipient cannot be the stream owner")]
     InvalidRecipient {},
 
     #[error("Numerical overflow")]
     Overflow {},
+
+    #[error("Invalid schedule: {reason}")]
+    InvalidSchedule { reason: String },
+
+    #[error("Exactly one native coin must be sent to create a stream")]
+    InvalidFunds {},
+
+    #[error("Cannot migrate from {previous_contract}")]
+    CannotMigrate { previous_contract: String },
+
+    #[error("Cannot migrate from newer version {previous_version}")]
+    CannotMigrateVersion { previous_version: String },
+
+    #[error("Semver parsing error: {0}")]
+    SemVer(String),
+}
+
+impl From<semver::Error> for ContractError {
+    fn from(err: semver::Error) -> Self {
+        Self::SemVer(err.to_string())
+    }
 }
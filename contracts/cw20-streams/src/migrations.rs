@@ -0,0 +1,142 @@
+// Migration logic for contracts with version: 0.14.2
+pub mod v0_14_2 {
+    use cosmwasm_schema::cw_serde;
+    use cosmwasm_std::{Addr, DepsMut, Order, StdError, StdResult, Storage, Uint128};
+    use cw20::Denom;
+    use cw_storage_plus::{Bound, Item, Map};
+    use serde::{Deserialize, Serialize};
+
+    use crate::msg::Schedule;
+    use crate::state::{streams, Config, Stream, CONFIG, STREAM_COUNT, STREAM_SEQ};
+    use crate::ContractError;
+
+    /// Config as stored when every instance was bound to a single cw20 token
+    #[cw_serde]
+    struct LegacyConfig {
+        owner: Addr,
+        cw20_addr: Addr,
+    }
+
+    /// Stream as stored before it had its own denom, when it still stored its rate
+    #[derive(Serialize, Deserialize)]
+    struct LegacyStream {
+        owner: Addr,
+        recipient: Addr,
+        #[serde(default)]
+        denom: Option<Denom>,
+        amount: Uint128,
+        claimed_amount: Uint128,
+        start_time: u64,
+        end_time: u64,
+        #[serde(default)]
+        schedule: Schedule,
+        #[serde(default)]
+        cancelled: bool,
+        #[serde(default)]
+        paused_at: Option<u64>,
+        #[serde(default)]
+        paused_duration: u64,
+    }
+
+    const LEGACY_CONFIG: Item<LegacyConfig> = Item::new("config");
+    const LEGACY_STREAMS: Map<u64, LegacyStream> = Map::new("stream");
+
+    /// How far rewriting the streams got, as it may take more than one migrate call
+    #[cw_serde]
+    struct Progress {
+        /// The cw20 token the contract used to be bound to
+        token: Option<Addr>,
+        /// The last stream rewritten so far
+        last_id: u64,
+        /// The last stream created before migrating, later ones are in the current format
+        end_id: u64,
+    }
+
+    const PROGRESS: Item<Progress> = Item::new("migrate_streams_progress");
+
+    pub fn migrate_config(deps: DepsMut) -> Result<(), ContractError> {
+        // contracts bound to a single cw20 token kept it in the config
+        let token = match LEGACY_CONFIG.load(deps.storage) {
+            Ok(legacy) => {
+                let config = Config {
+                    owner: legacy.owner,
+                };
+                CONFIG.save(deps.storage, &config)?;
+                Some(legacy.cw20_addr)
+            }
+            Err(_) => None,
+        };
+
+        // the streams are rewritten by `migrate_streams`, which counts them again
+        let progress = Progress {
+            token,
+            last_id: 0,
+            end_id: STREAM_SEQ.load(deps.storage)?,
+        };
+        PROGRESS.save(deps.storage, &progress)?;
+        STREAM_COUNT.save(deps.storage, &0)?;
+        Ok(())
+    }
+
+    /// Whether there are streams left to rewrite
+    pub fn migrating_streams(storage: &dyn Storage) -> StdResult<bool> {
+        Ok(PROGRESS.may_load(storage)?.is_some())
+    }
+
+    /// Rewrites up to `limit` more streams. Returns true once all are rewritten
+    pub fn migrate_streams(deps: DepsMut, limit: u32) -> Result<bool, ContractError> {
+        let mut progress = PROGRESS.load(deps.storage)?;
+        let legacy_streams = LEGACY_STREAMS
+            .range(
+                deps.storage,
+                Some(Bound::exclusive(progress.last_id)),
+                Some(Bound::inclusive(progress.end_id)),
+                Order::Ascending,
+            )
+            .take(limit as usize)
+            .collect::<StdResult<Vec<_>>>()?;
+        let batch_size = legacy_streams.len();
+        let mut count = 0u64;
+        for (id, stream) in legacy_streams {
+            if !stream.cancelled {
+                count += 1;
+            }
+            // streams without a denom are in the token the contract was bound to
+            let denom = match (stream.denom, &progress.token) {
+                (Some(denom), _) => denom,
+                (None, Some(token)) => Denom::Cw20(token.clone()),
+                (None, None) => {
+                    return Err(StdError::generic_err(format!("Stream {} has no denom", id)).into())
+                }
+            };
+            // the rate is not stored anymore, vesting is computed from the amount.
+            // unclaimed remainders were refunded on creation, so nothing changes for old streams
+            let stream = Stream {
+                owner: stream.owner,
+                recipient: stream.recipient,
+                denom,
+                amount: stream.amount,
+                claimed_amount: stream.claimed_amount,
+                start_time: stream.start_time,
+                end_time: stream.end_time,
+                schedule: stream.schedule,
+                cancelled: stream.cancelled,
+                paused_at: stream.paused_at,
+                paused_duration: stream.paused_duration,
+            };
+            // stored streams may not load in the current format, so do not look at the old
+            // value. This adds the owner and recipient indexes to streams created before them
+            streams().replace(deps.storage, id, Some(&stream), None)?;
+            progress.last_id = id;
+        }
+        STREAM_COUNT.update(deps.storage, |total| -> StdResult<_> { Ok(total + count) })?;
+
+        if batch_size < limit as usize || progress.last_id == progress.end_id {
+            PROGRESS.remove(deps.storage);
+            Ok(true)
+        } else {
+            PROGRESS.save(deps.storage, &progress)?;
+            Ok(false)
+        }
+    }
+}
@@ -1,19 +1,91 @@
 // use crate::state::Stream;
 use cosmwasm_schema::{cw_serde, QueryResponses};
-use cosmwasm_std::Uint128;
-use cw20::Cw20ReceiveMsg;
+use cosmwasm_std::{to_binary, Binary, Coin, CosmosMsg, Decimal, StdResult, Uint128, WasmMsg};
+use cw20::{Cw20ReceiveMsg, Denom};
+pub use cw721::{Cw721ReceiveMsg, NumTokensResponse, OwnerOfResponse, TokensResponse};
+
+/// How the streamed amount vests between start_time and end_time
+#[cw_serde]
+#[derive(Default)]
+pub enum Schedule {
+    /// Vests at a constant rate
+    #[default]
+    Linear,
+    /// Nothing vests before `cliff_time`. Then everything that vested linearly until then is
+    /// released at once, and the rest vests linearly
+    Cliff { cliff_time: u64 },
+    /// Vests in equal steps, one every `interval` seconds after start_time (eg. 2_592_000 for
+    /// 30 day months). The last step may be shorter and ends at end_time
+    Stepwise { interval: u64 },
+    /// Vests linearly between the given points, starting from nothing at start_time.
+    /// The last point must be at end_time with a share of 1
+    Piecewise { points: Vec<SchedulePoint> },
+}
+
+#[cw_serde]
+pub struct SchedulePoint {
+    pub time: u64,
+    /// The share of the amount vested by `time`
+    pub vested: Decimal,
+}
 
 #[cw_serde]
 pub struct InstantiateMsg {
     pub owner: Option<String>,
-    pub cw20_addr: String,
 }
 
 #[cw_serde]
 pub enum ExecuteMsg {
+    /// Creates a cw20 stream, in whichever token was sent
     Receive(Cw20ReceiveMsg),
+    /// Creates a native stream, funded with the one coin sent along
+    CreateStream {
+        recipient: String,
+        start_time: u64,
+        end_time: u64,
+        /// Defaults to linear
+        schedule: Option<Schedule>,
+    },
+    /// Only the recipient can withdraw. Defaults to everything that has vested, paid to
+    /// the recipient
     Withdraw {
         id: u64, // Stream id
+        amount: Option<Uint128>,
+        to: Option<String>,
+    },
+    /// Like `Withdraw`, but delivers the payout to a contract along with `msg`. Cw20 streams pay
+    /// with a cw20 `Send`, native streams call the contract with a `StreamReceiveMsg` and the
+    /// coins attached
+    WithdrawAndSend {
+        id: u64, // Stream id
+        amount: Option<Uint128>,
+        contract: String,
+        msg: Binary,
+    },
+    /// Only the stream owner can cancel. Pays out what has vested and refunds the rest
+    CancelStream {
+        id: u64, // Stream id
+    },
+    /// Only the stream owner can pause. Nothing vests until the stream is resumed
+    PauseStream {
+        id: u64, // Stream id
+    },
+    /// Only the stream owner can resume. The end time is pushed back by the length of the pause
+    ResumeStream {
+        id: u64, // Stream id
+    },
+    /// Only the recipient can transfer the stream, including what has vested but is not
+    /// withdrawn yet
+    TransferStream { id: u64, new_recipient: String },
+
+    /// Implements cw721, with the stream id as token id. Same as `TransferStream`
+    TransferNft { recipient: String, token_id: String },
+    /// Implements cw721, with the stream id as token id. Transfers the stream to a contract
+    /// and calls it with a `Cw721ReceiveMsg`
+    SendNft {
+        contract: String,
+        token_id: String,
+        msg: Binary,
     },
 }
 
@@ -23,6 +95,8 @@ pub enum ReceiveMsg {
         recipient: String,
         start_time: u64,
         end_time: u64,
+        /// Defaults to linear
+        schedule: Option<Schedule>,
     },
 }
 
@@ -30,9 +104,11 @@ pub enum ReceiveMsg {
 pub struct StreamParams {
     pub owner: String,
     pub recipient: String,
+    pub denom: Denom,
     pub amount: Uint128,
     pub start_time: u64,
     pub end_time: u64,
+    pub schedule: Schedule,
 }
 
 #[cw_serde]
@@ -44,15 +120,51 @@ pub enum QueryMsg {
     GetStream { id: u64 },
     #[returns(ListStreamsResponse)]
     ListStreams {
-        start: Option<u8>,
-        limit: Option<u8>,
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    },
+    /// Streams created by this owner, ordered by id
+    #[returns(ListStreamsResponse)]
+    StreamsByOwner {
+        owner: String,
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    },
+    /// Streams paying to this recipient, ordered by id
+    #[returns(ListStreamsResponse)]
+    StreamsByRecipient {
+        recipient: String,
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    },
+
+    /// Implements cw721. The recipient of the stream with this id
+    #[returns(OwnerOfResponse)]
+    OwnerOf {
+        token_id: String,
+        include_expired: Option<bool>,
+    },
+    /// Implements cw721. How many streams there are, not counting cancelled ones
+    #[returns(NumTokensResponse)]
+    NumTokens {},
+    /// Implements cw721. Ids of the streams with this recipient
+    #[returns(TokensResponse)]
+    Tokens {
+        owner: String,
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+    /// Implements cw721. Ids of all streams
+    #[returns(TokensResponse)]
+    AllTokens {
+        start_after: Option<String>,
+        limit: Option<u32>,
     },
 }
 
 #[cw_serde]
 pub struct ConfigResponse {
     pub owner: String,
-    pub cw20_addr: String,
 }
 
 #[cw_serde]
@@ -60,14 +172,69 @@ pub struct StreamResponse {
     pub id: u64,
     pub owner: String,
     pub recipient: String,
+    pub denom: Denom,
     pub amount: Uint128,
     pub claimed_amount: Uint128,
     pub start_time: u64,
     pub end_time: u64,
     pub rate_per_second: Uint128,
+    pub schedule: Schedule,
+    pub cancelled: bool,
+    pub paused: bool,
+    /// When the stream was paused, if it is paused now
+    pub paused_at: Option<u64>,
+    /// Seconds the stream has been paused after its start, not counting the current pause
+    pub paused_duration: u64,
 }
 
 #[cw_serde]
 pub struct ListStreamsResponse {
     pub streams: Vec<StreamResponse>,
 }
+
+#[cw_serde]
+pub struct MigrateMsg {
+    /// When migrating from 0.14.2 or older, this many streams are rewritten per migrate call
+    /// (default 100). Migrate again until the `streams_migrated` attribute is true
+    pub limit: Option<u32>,
+}
+
+/// StreamReceiveMsg should be de/serialized under `ReceiveStream()` variant in a ExecuteMsg.
+/// It is sent by `WithdrawAndSend` from native streams, with the withdrawn coins attached
+#[cw_serde]
+pub struct StreamReceiveMsg {
+    /// The stream recipient who withdrew
+    pub sender: String,
+    pub stream_id: u64,
+    pub amount: Uint128,
+    pub msg: Binary,
+}
+
+impl StreamReceiveMsg {
+    /// serializes the message
+    pub fn into_binary(self) -> StdResult<Binary> {
+        let msg = StreamReceiverExecuteMsg::ReceiveStream(self);
+        to_binary(&msg)
+    }
+
+    /// creates a cosmos_msg sending this struct to the named contract, along with funds
+    pub fn into_cosmos_msg<T: Into<String>>(
+        self,
+        contract_addr: T,
+        funds: Vec<Coin>,
+    ) -> StdResult<CosmosMsg> {
+        let msg = self.into_binary()?;
+        let execute = WasmMsg::Execute {
+            contract_addr: contract_addr.into(),
+            msg,
+            funds,
+        };
+        Ok(execute.into())
+    }
+}
+
+// This is just a helper to properly serialize the above message
+#[cw_serde]
+enum StreamReceiverExecuteMsg {
+    ReceiveStream(StreamReceiveMsg),
+}
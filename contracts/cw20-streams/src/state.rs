@@ -1,34 +1,72 @@
 use cosmwasm_schema::cw_serde;
 use cosmwasm_std::{Addr, DepsMut, StdResult, Uint128};
-use cw_storage_plus::{Item, Map};
+use cw20::Denom;
+use cw_storage_plus::{Index, IndexList, IndexedMap, Item, MultiIndex};
 use serde::{Deserialize, Serialize};
 
+use crate::msg::Schedule;
+
 #[cw_serde]
 pub struct Config {
     pub owner: Addr,
-    pub cw20_addr: Addr,
 }
 
 pub const CONFIG: Item<Config> = Item::new("config");
 
-#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
 pub struct Stream {
     pub owner: Addr,
     pub recipient: Addr,
+    /// The native coin or cw20 token that is streamed
+    pub denom: Denom,
     pub amount: Uint128,
     pub claimed_amount: Uint128,
     pub start_time: u64,
     pub end_time: u64,
-    pub rate_per_second: Uint128,
+    #[serde(default)]
+    pub schedule: Schedule,
+    /// Cancelled streams are settled and cannot be withdrawn from anymore
+    #[serde(default)]
+    pub cancelled: bool,
+    /// When the stream was paused, if it is paused now
+    #[serde(default)]
+    pub paused_at: Option<u64>,
+    /// Seconds the stream has been paused after its start, not counting the current pause.
+    /// The end time has been pushed back by as much
+    #[serde(default)]
+    pub paused_duration: u64,
 }
 
 pub const STREAM_SEQ: Item<u64> = Item::new("stream_seq");
-pub const STREAMS: Map<u64, Stream> = Map::new("stream");
+/// How many streams there are, not counting cancelled ones
+pub const STREAM_COUNT: Item<u64> = Item::new("stream_count");
+
+pub struct StreamIndexes<'a> {
+    pub owner: MultiIndex<'a, Addr, Stream, u64>,
+    pub recipient: MultiIndex<'a, Addr, Stream, u64>,
+}
+
+impl<'a> IndexList<Stream> for StreamIndexes<'a> {
+    fn get_indexes(&'_ self) -> Box<dyn Iterator<Item = &'_ dyn Index<Stream>> + '_> {
+        let v: Vec<&dyn Index<Stream>> = vec![&self.owner, &self.recipient];
+        Box::new(v.into_iter())
+    }
+}
+
+/// Streams by id, indexed by owner and recipient
+pub fn streams<'a>() -> IndexedMap<'a, u64, Stream, StreamIndexes<'a>> {
+    let indexes = StreamIndexes {
+        owner: MultiIndex::new(|_, s| s.owner.clone(), "stream", "stream__owner"),
+        recipient: MultiIndex::new(|_, s| s.recipient.clone(), "stream", "stream__recipient"),
+    };
+    IndexedMap::new("stream", indexes)
+}
 
 pub fn save_stream(deps: DepsMut, stream: &Stream) -> StdResult<u64> {
     let id = STREAM_SEQ.load(deps.storage)?;
     let id = id.checked_add(1).unwrap();
     STREAM_SEQ.save(deps.storage, &id)?;
-    STREAMS.save(deps.storage, id, stream)?;
+    STREAM_COUNT.update(deps.storage, |count| -> StdResult<_> { Ok(count + 1) })?;
+    streams().save(deps.storage, id, stream)?;
     Ok(id)
 }